@@ -0,0 +1,68 @@
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use rust_pacman::level::Level;
+use rust_pacman::maze::Maze;
+use rust_pacman::pathfinding::Graph;
+
+const SIZES: [usize; 3] = [64, 256, 512];
+const BUDGET: Duration = Duration::from_millis(500);
+
+// A lattice of pillars with a few longer walls, and a tunnel on every fourth row.
+fn large_level(size: usize) -> Level {
+    let mut source = String::from("pacman = 1 1\nmap\n");
+
+    for y in 0..size {
+        let row: String = (0..size)
+            .map(|x| {
+                let edge = x == 0 || x == size - 1;
+
+                if edge && y % 4 == 1 {
+                    't'
+                } else if edge || y == 0 || y == size - 1 || x % 2 == 0 && y % 2 == 0 && (x + y) % 12 != 0 {
+                    '1'
+                } else if y % 16 == 8 && x % 32 < 24 {
+                    '7'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        source.push_str(&row);
+        source.push('\n');
+    }
+
+    Level::parse(Path::new("bench.lvl"), &source).expect("generated level is valid")
+}
+
+fn bench<T>(name: &str, size: usize, mut run: impl FnMut() -> T) {
+    let start = Instant::now();
+    let mut iterations = 0u32;
+
+    while start.elapsed() < BUDGET {
+        black_box(run());
+        iterations += 1;
+    }
+
+    let per_run = start.elapsed() / iterations;
+    println!("{:<16} {:>4}x{:<4} {:>12?} per run ({} runs)", name, size, size, per_run, iterations);
+}
+
+fn main() {
+    for size in SIZES {
+        let level = large_level(size);
+        let maze = Maze::from_level(&level);
+        let graph = Graph::new(&maze, false);
+
+        let from = (1, 1);
+        let to = (size as i32 - 2, size as i32 - 3);
+        let crowded = |(x, y): (i32, i32)| if (x * 7 + y * 3) % 11 == 0 { None } else { Some(1 + (x % 3) as u32) };
+
+        bench("graph", size, || Graph::new(&maze, false));
+        bench("bfs", size, || graph.distances(from));
+        bench("a*", size, || graph.path(from, to));
+        bench("a* weighted", size, || graph.path_with(from, to, crowded));
+    }
+}
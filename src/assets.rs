@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sdl2::image::LoadTexture;
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
+
+pub const PACMAN: &str = "./res/pacman.png";
+
+pub const BLINKY: &str = "./res/blinky.png";
+pub const PINKY: &str = "./res/pinky.png";
+pub const INKY: &str = "./res/inky.png";
+pub const CLYDE: &str = "./res/clyde.png";
+
+pub const FRIGHTENED: &str = "./res/frightened.png";
+pub const EYES: &str = "./res/eyes.png";
+
+pub const CHERRY: &str = "./res/fruit/cherry.png";
+pub const STRAWBERRY: &str = "./res/fruit/strawberry.png";
+pub const ORANGE: &str = "./res/fruit/orange.png";
+pub const APPLE: &str = "./res/fruit/apple.png";
+pub const MELON: &str = "./res/fruit/melon.png";
+pub const GALAXIAN: &str = "./res/fruit/galaxian.png";
+pub const BELL: &str = "./res/fruit/bell.png";
+pub const KEY: &str = "./res/fruit/key.png";
+
+pub const ALL: [&str; 15] = [
+    PACMAN,
+    BLINKY,
+    PINKY,
+    INKY,
+    CLYDE,
+    FRIGHTENED,
+    EYES,
+    CHERRY,
+    STRAWBERRY,
+    ORANGE,
+    APPLE,
+    MELON,
+    GALAXIAN,
+    BELL,
+    KEY,
+];
+
+pub const INTRO: &str = "./res/sounds/intro.wav";
+pub const CHOMP: &str = "./res/sounds/chomp.wav";
+pub const DEATH: &str = "./res/sounds/death.wav";
+pub const FRUIT: &str = "./res/sounds/fruit.wav";
+
+pub const SIREN: [&str; 4] = [
+    "./res/sounds/siren0.wav",
+    "./res/sounds/siren1.wav",
+    "./res/sounds/siren2.wav",
+    "./res/sounds/siren3.wav",
+];
+
+pub const FRIGHTENED_LOOP: &str = "./res/sounds/frightened.wav";
+pub const EYES_LOOP: &str = "./res/sounds/eyes.wav";
+
+pub const SOUNDS: [&str; 10] = [
+    INTRO,
+    CHOMP,
+    DEATH,
+    FRUIT,
+    SIREN[0],
+    SIREN[1],
+    SIREN[2],
+    SIREN[3],
+    FRIGHTENED_LOOP,
+    EYES_LOOP,
+];
+
+pub fn check() -> io::Result<()> {
+    let missing: Vec<&str> = ALL
+        .iter()
+        .copied()
+        .filter(|path| !Path::new(path).is_file())
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    Err(io::Error::new(io::ErrorKind::NotFound, format!("Missing game assets: {}", missing.join(", "))))
+}
+
+pub struct Textures<'a> {
+    creator: &'a TextureCreator<WindowContext>,
+    textures: HashMap<PathBuf, Texture<'a>>
+}
+
+impl<'a> Textures<'a> {
+    pub fn new(creator: &'a TextureCreator<WindowContext>) -> Textures<'a> {
+        Textures {
+            creator,
+            textures: HashMap::new()
+        }
+    }
+
+    pub fn preload(&mut self, paths: &[&str]) -> io::Result<()> {
+        for path in paths {
+            self.get(Path::new(path))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&mut self, path: &Path) -> io::Result<&Texture<'a>> {
+        if !self.textures.contains_key(path) {
+            let texture = self.creator.load_texture(path)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Can't load texture {}: {}", path.display(), e)))?;
+
+            self.textures.insert(path.to_path_buf(), texture);
+        }
+
+        Ok(&self.textures[path])
+    }
+}
@@ -0,0 +1,251 @@
+use sdl2::Sdl;
+
+use crate::assets;
+use crate::events::GameEvent;
+use crate::game::Game;
+
+pub const MAX_VOLUME: u8 = 10;
+
+pub fn subscribe(game: &mut Game) {
+    game.subscribe(|game, event| {
+        if !game.is_demo()
+            && let Some(sound) = Sound::for_event(event) {
+            game.audio().play(sound);
+        }
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sound {
+    Intro,
+    Chomp,
+    Death,
+    Fruit
+}
+
+impl Sound {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Sound::Intro => assets::INTRO,
+            Sound::Chomp => assets::CHOMP,
+            Sound::Death => assets::DEATH,
+            Sound::Fruit => assets::FRUIT
+        }
+    }
+
+    pub fn for_event(event: &GameEvent) -> Option<Sound> {
+        match event {
+            GameEvent::PelletEaten { .. } | GameEvent::PowerPelletEaten { .. } => Some(Sound::Chomp),
+            GameEvent::PacmanDied => Some(Sound::Death),
+            GameEvent::FruitEaten { .. } => Some(Sound::Fruit),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Loop {
+    Siren(usize),
+    Frightened,
+    Eyes
+}
+
+impl Loop {
+    pub fn siren(remaining: usize, total: usize) -> Loop {
+        let stages = assets::SIREN.len();
+        let eaten = total.saturating_sub(remaining);
+        let stage = (eaten * stages).checked_div(total).unwrap_or(0);
+
+        Loop::Siren(stage.min(stages - 1))
+    }
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            Loop::Siren(stage) => assets::SIREN[*stage],
+            Loop::Frightened => assets::FRIGHTENED_LOOP,
+            Loop::Eyes => assets::EYES_LOOP
+        }
+    }
+}
+
+pub struct Audio {
+    muted: bool,
+    volume: u8,
+    current: Option<Loop>,
+    mixer: Option<mixer::Mixer>
+}
+
+impl Default for Audio {
+    fn default() -> Audio {
+        Audio::new()
+    }
+}
+
+impl Audio {
+    pub fn new() -> Audio {
+        Audio {
+            muted: false,
+            volume: MAX_VOLUME,
+            current: None,
+            mixer: None
+        }
+    }
+
+    pub fn open(&mut self, sdl: &Sdl) {
+        match mixer::Mixer::open(sdl) {
+            Ok(mixer) => {
+                self.mixer = Some(mixer);
+                self.apply_volume();
+            }
+
+            Err(e) => eprintln!("Audio disabled: {}", e)
+        }
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        if let Some(mixer) = &self.mixer {
+            mixer.play(sound.path(), sound == Sound::Chomp);
+        }
+    }
+
+    pub fn set_loop(&mut self, track: Option<Loop>) {
+        if self.current == track {
+            return;
+        }
+
+        self.current = track;
+
+        if let Some(mixer) = &self.mixer {
+            mixer.set_loop(track.map(|track| track.path()));
+        }
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volume();
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.set_muted(!self.muted);
+    }
+
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume.min(MAX_VOLUME);
+        self.apply_volume();
+    }
+
+    fn apply_volume(&self) {
+        if let Some(mixer) = &self.mixer {
+            let volume = if self.muted { 0 } else { self.volume };
+            mixer.set_volume(volume as f64 / MAX_VOLUME as f64);
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+mod mixer {
+    use std::collections::HashMap;
+
+    use sdl2::{AudioSubsystem, Sdl};
+    use sdl2::mixer::{self, Channel, Chunk};
+
+    use crate::assets;
+
+    const CHANNELS: i32 = 8;
+    const LOOP_CHANNEL: Channel = Channel(0);
+    const CHOMP_CHANNEL: Channel = Channel(1);
+    const RESERVED_CHANNELS: i32 = 2;
+
+    pub struct Mixer {
+        _audio: AudioSubsystem,
+        chunks: HashMap<&'static str, Chunk>
+    }
+
+    impl Mixer {
+        pub fn open(sdl: &Sdl) -> Result<Mixer, String> {
+            let audio = sdl.audio()?;
+            mixer::open_audio(mixer::DEFAULT_FREQUENCY, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, 1024)?;
+            mixer::allocate_channels(CHANNELS);
+            mixer::reserve_channels(RESERVED_CHANNELS);
+
+            let mut chunks = HashMap::new();
+            for path in assets::SOUNDS {
+                match Chunk::from_file(path) {
+                    Ok(chunk) => {
+                        chunks.insert(path, chunk);
+                    }
+
+                    Err(e) => eprintln!("Can't load sound {}: {}", path, e)
+                }
+            }
+
+            Ok(Mixer { _audio: audio, chunks })
+        }
+
+        pub fn play(&self, path: &str, chomp: bool) {
+            let Some(chunk) = self.chunks.get(path) else {
+                return;
+            };
+
+            // The chomp keeps its own channel so back to back pellets give one steady waka.
+            let channel = if chomp { CHOMP_CHANNEL } else { Channel::all() };
+            if chomp && channel.is_playing() {
+                return;
+            }
+
+            if let Err(e) = channel.play(chunk, 0) {
+                eprintln!("Can't play sound {}: {}", path, e);
+            }
+        }
+
+        pub fn set_loop(&self, path: Option<&str>) {
+            LOOP_CHANNEL.halt();
+
+            let Some(chunk) = path.and_then(|path| self.chunks.get(path)) else {
+                return;
+            };
+
+            if let Err(e) = LOOP_CHANNEL.play(chunk, -1) {
+                eprintln!("Can't play sound loop: {}", e);
+            }
+        }
+
+        pub fn set_volume(&self, volume: f64) {
+            Channel::all().set_volume((volume * mixer::MAX_VOLUME as f64).round() as i32);
+        }
+    }
+
+    impl Drop for Mixer {
+        fn drop(&mut self) {
+            self.chunks.clear();
+            mixer::close_audio();
+        }
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+mod mixer {
+    use sdl2::Sdl;
+
+    pub struct Mixer;
+
+    impl Mixer {
+        pub fn open(_: &Sdl) -> Result<Mixer, String> {
+            Ok(Mixer)
+        }
+
+        pub fn play(&self, _: &str, _: bool) {}
+
+        pub fn set_loop(&self, _: Option<&str>) {}
+
+        pub fn set_volume(&self, _: f64) {}
+    }
+}
@@ -1,11 +1,9 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
-use crate::entity::{Entity, EntityEnviroment};
+use crate::entity::{Collider, Entity, Message, Tag};
 use crate::graphics::{Graphics, Line};
+use crate::input::Action;
+use crate::world::{Commands, World};
 
 use sdl2::pixels::Color;
-use sdl2::keyboard::Keycode;
 
 #[derive(Clone)]
 pub enum BlockStyle {
@@ -19,31 +17,56 @@ pub enum BlockStyle {
     BottomRight,
     BottomLeft,
     TopRight,
-    TopLeft
+    TopLeft,
+    Door
 }
 
 impl BlockStyle {
-    pub fn convert(c: char) -> BlockStyle {
+    pub fn convert(c: char) -> Option<BlockStyle> {
         match c {
-            '1' => BlockStyle::Full,
-            '2' => BlockStyle::Top,
-            '3' => BlockStyle::Bottom,
-            '4' => BlockStyle::Left,
-            '5' => BlockStyle::Right,
-            '6' => BlockStyle::TopBottom,
-            '7' => BlockStyle::LeftRight,
-            '8' => BlockStyle::BottomRight,
-            '9' => BlockStyle::BottomLeft,
-            'a' => BlockStyle::TopRight,
-            'b' => BlockStyle::TopLeft,
-            _ => unreachable!()
+            '1' => Some(BlockStyle::Full),
+            '2' => Some(BlockStyle::Top),
+            '3' => Some(BlockStyle::Bottom),
+            '4' => Some(BlockStyle::Left),
+            '5' => Some(BlockStyle::Right),
+            '6' => Some(BlockStyle::TopBottom),
+            '7' => Some(BlockStyle::LeftRight),
+            '8' => Some(BlockStyle::BottomRight),
+            '9' => Some(BlockStyle::BottomLeft),
+            'a' => Some(BlockStyle::TopRight),
+            'b' => Some(BlockStyle::TopLeft),
+            '-' => Some(BlockStyle::Door),
+            _ => None
+        }
+    }
+
+    pub fn connects(&self, dir: (i8, i8)) -> bool {
+        let (up, left, down, right) = match self {
+            BlockStyle::Full => (false, false, false, false),
+            BlockStyle::Top => (true, false, false, false),
+            BlockStyle::Bottom => (false, false, true, false),
+            BlockStyle::Left => (false, true, false, false),
+            BlockStyle::Right => (false, false, false, true),
+            BlockStyle::TopBottom => (true, false, true, false),
+            BlockStyle::LeftRight | BlockStyle::Door => (false, true, false, true),
+            BlockStyle::BottomRight => (false, false, true, true),
+            BlockStyle::BottomLeft => (false, true, true, false),
+            BlockStyle::TopRight => (true, false, false, true),
+            BlockStyle::TopLeft => (true, true, false, false)
+        };
+
+        match dir {
+            (0, -1) => up,
+            (-1, 0) => left,
+            (0, 1) => down,
+            (1, 0) => right,
+            _ => false
         }
     }
 }
 
 #[derive(Clone)]
 pub struct Block {
-    name: String,
     x: i32,
     y: i32,
     rect: Graphics,
@@ -123,27 +146,36 @@ impl Block {
                     lines.push(Line::create((25, 0), (25, 25)));
                     lines.push(Line::create((0, 25), (25, 25)));
                 }
+
+                BlockStyle::Door => {
+                    lines.push(Line::create((0, 14), (32, 14)));
+                    lines.push(Line::create((0, 18), (32, 18)));
+                }
             }
         }
         
     }
     
-    pub fn new(name: &str, x: i32, y: i32, style: BlockStyle) -> Block {    
+    pub fn new(x: i32, y: i32, style: BlockStyle) -> Block {    
+        let color = match style {
+            BlockStyle::Door => Color::RGB(255, 184, 255),
+            _ => Color::RGB(0, 255, 255)
+        };
+
         Block {
-            name: name.to_string(),
-            x: x,
-            y: y,
+            x,
+            y,
             rect: Graphics::Line(Vec::new()),
-            color: Color::RGB(0, 255, 255),
-            style: style
+            color,
+            style
         }
     }
 }
 
 
 impl Entity for Block {
-    fn get_name(&self) -> &str {
-        &self.name
+    fn tag(&self) -> Tag {
+        Tag::Block
     }
 
     fn get_graphics(&self) -> &Graphics {
@@ -154,15 +186,25 @@ impl Entity for Block {
         (self.x, self.y)
     }
 
-    fn get_color(&mut self) -> &Color {
+    fn get_dir(&self) -> (i8, i8) {
+        (0, 0)
+    }
+
+    fn get_collider(&self) -> Option<Collider> {
+        None
+    }
+
+    fn get_color(&self) -> &Color {
         &self.color
     }
 
-    fn start(&mut self, _: Rc<RefCell<EntityEnviroment>>) {
+    fn start(&mut self, _: &World, _: &mut Commands) {
         self.generate_style();
     }
     
-    fn update(&mut self, _: Rc<RefCell<EntityEnviroment>>) { }
+    fn update(&mut self, _: &World, _: &mut Commands) { }
+
+    fn on_action(&mut self, _: Action) { }
 
-    fn on_key_down(&mut self, _: Keycode) { }
+    fn on_message(&mut self, _: &Message) { }
 }
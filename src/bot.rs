@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use crate::entity::Tag;
+use crate::input::Action;
+use crate::maze::Maze;
+use crate::pathfinding::{DistanceMap, Graph};
+use crate::world::World;
+
+const DANGER_DISTANCE: u32 = 3;
+const CAUTION_DISTANCE: u32 = 6;
+const CAUTION_COST: u32 = 8;
+const HUNT_DISTANCE: u32 = 10;
+const TARGET_TRIES: usize = 6;
+
+pub struct Bot {
+    maze: Rc<Maze>,
+    graph: Graph
+}
+
+impl Bot {
+    pub fn new(maze: Rc<Maze>) -> Bot {
+        let graph = Graph::new(&maze, false);
+
+        Bot { maze, graph }
+    }
+
+    fn tile_of(&self, pos: (i32, i32)) -> (i32, i32) {
+        self.maze.wrap_tile(self.maze.tile_at(pos))
+    }
+
+    pub fn decide(&self, world: &World, pellets: &[(i32, i32)]) -> Option<Action> {
+        let pacman = world.first(Tag::Pacman)?;
+        let here = self.tile_of(pacman.pos);
+
+        // Ghosts without a collider are waiting in the house or heading back as eyes.
+        let mut threats: Vec<DistanceMap> = Vec::new();
+        let mut prey = Vec::new();
+
+        for ghost in world.tagged(Tag::Ghost).filter(|ghost| ghost.collider.is_some()) {
+            let tile = self.tile_of(ghost.pos);
+
+            if ghost.frightened {
+                prey.push(tile);
+            } else {
+                threats.push(self.graph.distances(tile));
+            }
+        }
+
+        let danger = |tile: (i32, i32)| threats.iter().filter_map(|map| map.get(tile)).min();
+        let cost = |tile: (i32, i32)| match danger(tile) {
+            Some(distance) if distance <= DANGER_DISTANCE => None,
+            Some(distance) if distance <= CAUTION_DISTANCE => Some(CAUTION_COST),
+            _ => Some(1)
+        };
+
+        let reach = self.graph.distances(here);
+        let nearest = |tiles: &[(i32, i32)]| {
+            let mut tiles: Vec<((i32, i32), u32)> = tiles
+                .iter()
+                .filter_map(|tile| reach.get(*tile).map(|distance| (*tile, distance)))
+                .collect();
+
+            tiles.sort_by_key(|(_, distance)| *distance);
+            tiles
+        };
+
+        let mut targets: Vec<(i32, i32)> = nearest(&prey)
+            .into_iter()
+            .filter(|(_, distance)| *distance <= HUNT_DISTANCE)
+            .map(|(tile, _)| tile)
+            .collect();
+
+        let food: Vec<(i32, i32)> = pellets
+            .iter()
+            .chain(world.tagged(Tag::Fruit).map(|fruit| &fruit.pos))
+            .map(|pos| self.tile_of(*pos))
+            .collect();
+        targets.extend(nearest(&food).into_iter().map(|(tile, _)| tile));
+
+        let step = targets
+            .iter()
+            .take(TARGET_TRIES)
+            .find_map(|target| self.graph.path_with(here, *target, cost))
+            .and_then(|path| path.get(1).copied());
+
+        // With every way forward too close to a ghost, run to whichever neighbour is furthest from them.
+        let step = step.or_else(|| {
+            self.graph
+                .neighbours(here)
+                .max_by_key(|(_, tile)| danger(*tile).unwrap_or(u32::MAX))
+                .map(|(_, tile)| tile)
+        })?;
+
+        let (dir, _) = self.graph.neighbours(here).find(|(_, tile)| *tile == step)?;
+
+        if dir == pacman.dir {
+            return None;
+        }
+
+        Action::from_direction(dir)
+    }
+}
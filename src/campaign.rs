@@ -0,0 +1,158 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::files;
+use crate::fruit::FruitKind;
+use crate::game::TICKS_PER_SECOND;
+use crate::ghost::GHOST_SPEED;
+
+pub const START_LIVES: u32 = 3;
+const GHOST_SPEED_STEP: f64 = 0.75;
+const GHOST_SPEED_MAX: f64 = 7.5;
+
+const POWER_PELLETS: usize = 4;
+const POWER_PELLETS_MIN: usize = 1;
+
+const FRIGHTENED_SECONDS: u32 = 6;
+const FRIGHTENED_SECONDS_MIN: u32 = 1;
+
+const HOUSE_PELLETS: [[usize; 3]; 3] = [[0, 30, 60], [0, 0, 50], [0, 0, 0]];
+const HOUSE_IDLE_SECONDS: u32 = 4;
+const HOUSE_IDLE_SECONDS_LATE: u32 = 3;
+const HOUSE_IDLE_LATE_LEVEL: usize = 4;
+
+pub struct Difficulty {
+    pub ghost_speed: f64,
+    pub power_pellets: usize,
+    pub frightened_ticks: u32,
+    pub fruit: FruitKind,
+    pub house_pellets: [usize; 3],
+    pub house_idle_ticks: u32
+}
+
+impl Difficulty {
+    pub fn for_level(index: usize) -> Difficulty {
+        let frightened_seconds = FRIGHTENED_SECONDS.saturating_sub(index as u32).max(FRIGHTENED_SECONDS_MIN);
+        let house_idle_seconds = if index < HOUSE_IDLE_LATE_LEVEL { HOUSE_IDLE_SECONDS } else { HOUSE_IDLE_SECONDS_LATE };
+
+        Difficulty {
+            ghost_speed: (GHOST_SPEED + index as f64 * GHOST_SPEED_STEP).min(GHOST_SPEED_MAX),
+            power_pellets: POWER_PELLETS.saturating_sub(index).max(POWER_PELLETS_MIN),
+            frightened_ticks: frightened_seconds * TICKS_PER_SECOND,
+            fruit: FruitKind::for_level(index),
+            house_pellets: HOUSE_PELLETS[index.min(HOUSE_PELLETS.len() - 1)],
+            house_idle_ticks: house_idle_seconds * TICKS_PER_SECOND
+        }
+    }
+}
+
+pub struct Campaign {
+    levels: Vec<PathBuf>,
+    current: usize,
+    score: u32,
+    lives: u32,
+    pellets: u32
+}
+
+impl Campaign {
+    pub fn new(levels: Vec<PathBuf>, lives: u32) -> Campaign {
+        Campaign {
+            levels,
+            current: 0,
+            score: 0,
+            lives,
+            pellets: 0
+        }
+    }
+
+    pub fn level_path(&self) -> &Path {
+        &self.levels[self.current]
+    }
+
+    pub fn stage(&self) -> usize {
+        self.current
+    }
+
+    pub fn level_number(&self) -> usize {
+        self.current + 1
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::for_level(self.current)
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn pellets(&self) -> u32 {
+        self.pellets
+    }
+
+    pub fn advance(&mut self, score: u32, lives: u32, pellets: u32) -> bool {
+        self.score = score;
+        self.lives = lives;
+        self.pellets = pellets;
+        self.current += 1;
+
+        self.current < self.levels.len()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut data = String::new();
+        data.push_str(&format!("current = {}\n", self.current));
+        data.push_str(&format!("score = {}\n", self.score));
+        data.push_str(&format!("lives = {}\n", self.lives));
+        data.push_str(&format!("pellets = {}\n", self.pellets));
+
+        for level in self.levels.iter() {
+            data.push_str(&format!("level = {}\n", level.display()));
+        }
+
+        files::write_atomic(path, &data)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Campaign> {
+        let data = fs::read_to_string(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), message));
+
+        let mut campaign = Campaign::new(Vec::new(), START_LIVES);
+
+        for line in data.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let value = value.trim();
+            let number = || value.parse::<u32>().map_err(|_| invalid(&format!("bad number `{}`", value)));
+
+            match key.trim() {
+                "current" => campaign.current = number()? as usize,
+                "score" => campaign.score = number()?,
+                "lives" => campaign.lives = number()?,
+                "pellets" => campaign.pellets = number()?,
+                "level" => campaign.levels.push(PathBuf::from(value)),
+                key => return Err(invalid(&format!("unknown key `{}`", key)))
+            }
+        }
+
+        if campaign.current >= campaign.levels.len() {
+            return Err(invalid("saved level is out of range"));
+        }
+
+        if campaign.lives == 0 {
+            return Err(invalid("no lives left"));
+        }
+
+        Ok(campaign)
+    }
+
+    pub fn clear_save(path: &Path) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,48 +1,226 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
 
 use sdl2::pixels::Color;
-use sdl2::keyboard::Keycode;
 
+use crate::events::GameEvent;
 use crate::graphics::Graphics;
+use crate::input::Action;
+use crate::world::{Commands, World};
+
+pub enum Message {
+    Frighten(u32),
+    Eaten,
+    Release,
+    Reset
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct EntityId(u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tag {
+    Pacman,
+    Ghost,
+    Block,
+    Fruit
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Collider {
+    pub half_size: i32
+}
+
+impl Collider {
+    pub fn overlaps(&self, pos: (i32, i32), other: &Collider, other_pos: (i32, i32)) -> bool {
+        let reach = self.half_size + other.half_size;
+
+        (pos.0 - other_pos.0).abs() < reach && (pos.1 - other_pos.1).abs() < reach
+    }
+}
+
+pub trait Component {
+    type Value<'a>;
+
+    fn read(entity: &dyn Entity) -> Option<Self::Value<'_>>;
+}
+
+pub struct Position;
+pub struct Velocity;
+pub struct Sprite;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct GhostStatus {
+    pub frightened: bool,
+    pub returning: bool
+}
+
+impl Component for Position {
+    type Value<'a> = (i32, i32);
+
+    fn read(entity: &dyn Entity) -> Option<(i32, i32)> {
+        Some(entity.get_pos())
+    }
+}
+
+impl Component for Velocity {
+    type Value<'a> = (i8, i8);
+
+    fn read(entity: &dyn Entity) -> Option<(i8, i8)> {
+        Some(entity.get_dir())
+    }
+}
+
+impl Component for Sprite {
+    type Value<'a> = (&'a Graphics, Color);
+
+    fn read(entity: &dyn Entity) -> Option<(&Graphics, Color)> {
+        Some((entity.get_graphics(), *entity.get_color()))
+    }
+}
+
+impl Component for GhostStatus {
+    type Value<'a> = GhostStatus;
+
+    fn read(entity: &dyn Entity) -> Option<GhostStatus> {
+        entity.component(TypeId::of::<GhostStatus>())?.downcast_ref().copied()
+    }
+}
+
+impl Component for Collider {
+    type Value<'a> = Collider;
+
+    fn read(entity: &dyn Entity) -> Option<Collider> {
+        entity.get_collider()
+    }
+}
+
+impl Component for Tag {
+    type Value<'a> = Tag;
+
+    fn read(entity: &dyn Entity) -> Option<Tag> {
+        Some(entity.tag())
+    }
+}
 
 pub struct EntityEnviroment {
-    entities: Vec<Box<dyn Entity>>
+    next_id: u32,
+    entities: Vec<(EntityId, Box<dyn Entity>)>,
+    tags: HashMap<Tag, Vec<EntityId>>
 }
 
 impl EntityEnviroment {
     pub fn create() -> EntityEnviroment {
-        EntityEnviroment { entities: Vec::new() }
+        EntityEnviroment {
+            next_id: 0,
+            entities: Vec::new(),
+            tags: HashMap::new()
+        }
     }
     
-    pub fn add(&mut self, entity: Box<dyn Entity>) {
-        self.entities.push(entity);
-    }
+    pub fn add(&mut self, entity: Box<dyn Entity>) -> EntityId {
+        let id = EntityId(self.next_id);
+        self.next_id += 1;
+
+        self.tags.entry(entity.tag()).or_default().push(id);
+        self.entities.push((id, entity));
 
-    pub fn get_mut(&mut self) -> &mut Vec<Box<dyn Entity>> {
-        &mut self.entities
+        id
     }
 
-    pub fn get_first(&self, name: &str) -> Option<&Box<dyn Entity>> {
-        for ent in self.entities.iter() {
-            if ent.get_name() == name {
-                return Some(ent);
-            }
+    pub fn remove(&mut self, id: EntityId) -> Option<Box<dyn Entity>> {
+        let index = self.index(id)?;
+        let (_, entity) = self.entities.remove(index);
+
+        if let Some(ids) = self.tags.get_mut(&entity.tag()) {
+            ids.retain(|other| *other != id);
         }
 
-        None
+        Some(entity)
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.tags.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn index(&self, id: EntityId) -> Option<usize> {
+        self.entities.binary_search_by_key(&id, |(id, _)| *id).ok()
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&dyn Entity> {
+        let index = self.index(id)?;
+        Some(self.entities[index].1.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut dyn Entity> {
+        let index = self.index(id)?;
+        Some(self.entities[index].1.as_mut())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &dyn Entity)> {
+        self.entities.iter().map(|(id, entity)| (*id, entity.as_ref()))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EntityId, &mut dyn Entity)> {
+        self.entities.iter_mut().map(|(id, entity)| (*id, entity.as_mut() as &mut dyn Entity))
+    }
+
+    pub fn tagged(&self, tag: Tag) -> impl Iterator<Item = (EntityId, &dyn Entity)> {
+        self.tags
+            .get(&tag)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.get(*id).map(|entity| (*id, entity)))
+    }
+
+    pub fn first(&self, tag: Tag) -> Option<(EntityId, &dyn Entity)> {
+        self.tagged(tag).next()
+    }
+
+    pub fn component<C: Component>(&self, id: EntityId) -> Option<C::Value<'_>> {
+        C::read(self.get(id)?)
+    }
+
+    pub fn query<C: Component>(&self) -> impl Iterator<Item = (EntityId, C::Value<'_>)> {
+        self.iter().filter_map(|(id, entity)| C::read(entity).map(|value| (id, value)))
+    }
+
+    pub fn query_tagged<C: Component>(&self, tag: Tag) -> impl Iterator<Item = (EntityId, C::Value<'_>)> {
+        self.tagged(tag).filter_map(|(id, entity)| C::read(entity).map(|value| (id, value)))
     }
 }
 
 pub trait Entity {
-    fn get_name(&self) -> &str;
+    fn tag(&self) -> Tag;
     fn get_pos(&self) -> (i32, i32);
+    fn get_dir(&self) -> (i8, i8);
+    fn get_collider(&self) -> Option<Collider>;
 
     fn get_graphics(&self) -> &Graphics;
-    fn get_color(&mut self) -> &Color;
+    fn get_color(&self) -> &Color;
 
-    fn start(&mut self, env: Rc<RefCell<EntityEnviroment>>);
-    fn update(&mut self, env: Rc<RefCell<EntityEnviroment>>);
+    // Components only some entities carry, like a ghost's status, are looked up by their type.
+    fn component(&self, _: TypeId) -> Option<&dyn Any> {
+        None
+    }
+
+    fn start(&mut self, world: &World, commands: &mut Commands);
+    fn update(&mut self, world: &World, commands: &mut Commands);
 
-    fn on_key_down(&mut self, keycode: Keycode);
+    fn on_action(&mut self, action: Action);
+    fn on_message(&mut self, message: &Message);
+    fn on_event(&mut self, _: &GameEvent) {}
+
+    fn is_alive(&self) -> bool {
+        true
+    }
 }
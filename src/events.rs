@@ -0,0 +1,54 @@
+use std::collections::VecDeque;
+
+use crate::entity::EntityId;
+use crate::fruit::FruitKind;
+use crate::game::Game;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    PelletEaten { pos: (i32, i32) },
+    PowerPelletEaten { pos: (i32, i32) },
+    GhostEaten { id: EntityId },
+    PacmanDied,
+    LevelCleared,
+    FruitSpawned { pos: (i32, i32) },
+    FruitEaten { id: EntityId, kind: FruitKind }
+}
+
+pub type Handler = fn(&mut Game, &GameEvent);
+
+// Events wait here until `Game` hands each one to the subscribed handlers, in the order they subscribed, and then to the entities.
+#[derive(Default)]
+pub struct EventQueue {
+    pending: VecDeque<GameEvent>,
+    handlers: Vec<Handler>
+}
+
+impl EventQueue {
+    pub fn new() -> EventQueue {
+        EventQueue {
+            pending: VecDeque::new(),
+            handlers: Vec::new()
+        }
+    }
+
+    pub fn subscribe(&mut self, handler: Handler) {
+        self.handlers.push(handler);
+    }
+
+    pub fn handlers(&self) -> &[Handler] {
+        &self.handlers
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        self.pending.push_back(event);
+    }
+
+    pub fn pop(&mut self) -> Option<GameEvent> {
+        self.pending.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
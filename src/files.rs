@@ -0,0 +1,14 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Writes next to the target first so a crash part way through leaves the old file intact.
+pub fn write_atomic(path: &Path, data: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, data)?;
+    fs::rename(&temp, path)
+}
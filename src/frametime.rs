@@ -0,0 +1,50 @@
+use std::time::{Duration, Instant};
+
+const WINDOW: u32 = 60;
+
+pub struct FrameTimer {
+    started: Option<Instant>,
+    frames: u32,
+    total: Duration,
+    average: Duration
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            started: None,
+            frames: 0,
+            total: Duration::ZERO,
+            average: Duration::ZERO
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    pub fn end(&mut self) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+
+        self.total += started.elapsed();
+        self.frames += 1;
+
+        if self.frames == WINDOW {
+            self.average = self.total / WINDOW;
+            self.total = Duration::ZERO;
+            self.frames = 0;
+        }
+    }
+
+    pub fn average(&self) -> Duration {
+        self.average
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> FrameTimer {
+        FrameTimer::new()
+    }
+}
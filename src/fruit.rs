@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use sdl2::pixels::Color;
+
+use crate::assets;
+use crate::entity::{Collider, Entity, Message, Tag};
+use crate::game::TICKS_PER_SECOND;
+use crate::graphics::Graphics;
+use crate::input::Action;
+use crate::world::{Commands, World};
+
+pub const FRUIT_TICKS: u32 = 10 * TICKS_PER_SECOND;
+
+const FRUIT_COLLIDER: Collider = Collider { half_size: 8 };
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FruitKind {
+    Cherry,
+    Strawberry,
+    Orange,
+    Apple,
+    Melon,
+    Galaxian,
+    Bell,
+    Key
+}
+
+impl FruitKind {
+    pub fn for_level(index: usize) -> FruitKind {
+        match index {
+            0 => FruitKind::Cherry,
+            1 => FruitKind::Strawberry,
+            2 | 3 => FruitKind::Orange,
+            4 | 5 => FruitKind::Apple,
+            6 | 7 => FruitKind::Melon,
+            8 | 9 => FruitKind::Galaxian,
+            10 | 11 => FruitKind::Bell,
+            _ => FruitKind::Key
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            FruitKind::Cherry => 100,
+            FruitKind::Strawberry => 300,
+            FruitKind::Orange => 500,
+            FruitKind::Apple => 700,
+            FruitKind::Melon => 1000,
+            FruitKind::Galaxian => 2000,
+            FruitKind::Bell => 3000,
+            FruitKind::Key => 5000
+        }
+    }
+
+    pub fn image(&self) -> &'static str {
+        match self {
+            FruitKind::Cherry => assets::CHERRY,
+            FruitKind::Strawberry => assets::STRAWBERRY,
+            FruitKind::Orange => assets::ORANGE,
+            FruitKind::Apple => assets::APPLE,
+            FruitKind::Melon => assets::MELON,
+            FruitKind::Galaxian => assets::GALAXIAN,
+            FruitKind::Bell => assets::BELL,
+            FruitKind::Key => assets::KEY
+        }
+    }
+}
+
+pub struct Fruit {
+    x: i32,
+    y: i32,
+    graphics: Graphics,
+    color: Color,
+    ticks_left: u32
+}
+
+impl Fruit {
+    pub fn new(kind: FruitKind, x: i32, y: i32) -> Fruit {
+        Fruit {
+            x,
+            y,
+            graphics: Graphics::Image { path: PathBuf::from(kind.image()) },
+            color: Color::WHITE,
+            ticks_left: FRUIT_TICKS
+        }
+    }
+}
+
+impl Entity for Fruit {
+    fn tag(&self) -> Tag {
+        Tag::Fruit
+    }
+
+    fn get_pos(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+
+    fn get_dir(&self) -> (i8, i8) {
+        (0, 0)
+    }
+
+    fn get_collider(&self) -> Option<Collider> {
+        Some(FRUIT_COLLIDER)
+    }
+
+    fn get_graphics(&self) -> &Graphics {
+        &self.graphics
+    }
+
+    fn get_color(&self) -> &Color {
+        &self.color
+    }
+
+    fn start(&mut self, _: &World, _: &mut Commands) { }
+
+    fn update(&mut self, _: &World, _: &mut Commands) {
+        self.ticks_left = self.ticks_left.saturating_sub(1);
+    }
+
+    fn on_action(&mut self, _: Action) { }
+
+    fn on_message(&mut self, message: &Message) {
+        if let Message::Reset = message {
+            self.ticks_left = 0;
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.ticks_left > 0
+    }
+}
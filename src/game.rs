@@ -1,75 +1,316 @@
 extern crate sdl2;
 
-use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::thread;
 use std::io;
 
 use sdl2::pixels::Color;
 use sdl2::event::Event;
+use sdl2::gfx::primitives::DrawRenderer;
 use sdl2::keyboard::Keycode;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
-use sdl2::image::{InitFlag, LoadTexture};
 
-use crate::entity::Entity;
-use crate::entity::EntityEnviroment;
+use crate::assets::{self, Textures};
+use crate::audio::{self, Audio, Loop, Sound};
+use crate::bot::Bot;
+use crate::entity::{Entity, EntityEnviroment, EntityId, GhostStatus, Message, Position, Sprite, Tag, Velocity};
+use crate::events::{EventQueue, GameEvent, Handler};
+use crate::font;
+use crate::frametime::FrameTimer;
+use crate::fruit::{Fruit, FruitKind};
 use crate::graphics::Graphics;
+use crate::house::{self, GhostHouse};
+use crate::hud::Hud;
+use crate::initials::InitialsEntry;
+use crate::input::Action;
+use crate::level::BLOCK_SIZE;
+use crate::maze::Maze;
+use crate::replay::{self, Divergence, Playback, Replay};
+use crate::rng::Rng;
+use crate::options::{self, OptionsMenu};
+use crate::screen::{FrameRate, Screen};
+use crate::settings::Settings;
+use crate::world::{Command, Commands, World};
 
+pub const TICKS_PER_SECOND: u32 = 60;
+
+const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND as u64);
+const MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+const PELLET_POINTS: u32 = 10;
+const POWER_PELLET_POINTS: u32 = 50;
+const GHOST_POINTS: u32 = 200;
+const GHOST_POINTS_MAX: u32 = 1600;
+
+const READY_TICKS: u32 = 2 * TICKS_PER_SECOND;
+const DEATH_TICKS: u32 = 3 * TICKS_PER_SECOND / 2;
+const LEVEL_CLEAR_TICKS: u32 = 2 * TICKS_PER_SECOND;
+const LEVEL_CLEAR_FLASH: u32 = 15;
+const GAME_OVER_TICKS: u32 = 3 * TICKS_PER_SECOND;
+const ATTRACT_TICKS: u32 = 10 * TICKS_PER_SECOND;
+
+type System = fn(&mut Game);
+
+const PLAY_SYSTEMS: [System; 6] = [
+    Game::collide,
+    Game::update,
+    Game::eat_pellets,
+    Game::release_ghosts,
+    Game::spawn_fruit,
+    Game::check_level_clear,
+];
+
+const FRUIT_PELLETS: [usize; 2] = [35, 85];
+const HUD_FRUITS: usize = 7;
+
+const BANNER_SCALE: i32 = 3;
+const TITLE_SCALE: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameState {
+    Title,
+    Ready,
+    Playing,
+    Paused,
+    Options,
+    Initials,
+    Dying,
+    LevelClear,
+    GameOver,
     Win,
     Lose,
     Close
 }
 
+impl GameState {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, GameState::Win | GameState::Lose | GameState::Close)
+    }
+}
+
 pub struct Game {
     title: String,
     width: u32,
     height: u32,
-    entity_enviroment: Rc<RefCell<EntityEnviroment>>,
-    scores: Vec<(i32, i32)>
+    entity_enviroment: EntityEnviroment,
+    events: EventQueue,
+    commands: Commands,
+    maze: Rc<Maze>,
+    bot: Option<Bot>,
+    demo: bool,
+    scores: Vec<(i32, i32)>,
+    power_pellets: Vec<(i32, i32)>,
+    pellet_total: usize,
+    frightened_ticks: u32,
+    house: GhostHouse,
+    fruit_spawn: Option<(i32, i32)>,
+    fruit: FruitKind,
+    fruits_spawned: usize,
+    ghost_points: u32,
+    score: u32,
+    pellets_eaten: u32,
+    high_score: u32,
+    lives: u32,
+    level: usize,
+    ticks: u64,
+    seed: u64,
+    rng: Rng,
+    recording: Option<Replay>,
+    playback: Option<Playback>,
+    state: GameState,
+    state_ticks: u32,
+    screen: Option<Screen>,
+    audio: Audio,
+    settings: Settings,
+    options: Option<OptionsMenu>,
+    initials: Option<InitialsEntry>,
+    frame_timer: FrameTimer,
+    show_frame_time: bool,
+    frame_rate: FrameRate,
+    previous: HashMap<EntityId, (i32, i32)>
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldState {
+    pub pacman: Option<(i32, i32)>,
+    pub ghosts: Vec<(i32, i32)>,
+    pub pellets: Vec<(i32, i32)>,
+    pub power_pellets: Vec<(i32, i32)>,
+    pub score: u32,
+    pub lives: u32,
+    pub ticks: u64
 }
 
 impl Game {
     pub fn create(title: &str, width: u32, height: u32) -> Game {
-        Game {
+        let mut game = Game {
             title: title.to_string(),
-            width: width,
-            height: height,
-            entity_enviroment: Rc::new(RefCell::new(EntityEnviroment::create())),
+            width,
+            height,
+            entity_enviroment: EntityEnviroment::create(),
+            events: EventQueue::new(),
+            commands: Commands::new(),
+            maze: Rc::new(Maze::default()),
+            bot: None,
+            demo: false,
             scores: Vec::new(),
+            power_pellets: Vec::new(),
+            pellet_total: 0,
+            frightened_ticks: 0,
+            house: GhostHouse::default(),
+            fruit_spawn: None,
+            fruit: FruitKind::Cherry,
+            fruits_spawned: 0,
+            ghost_points: GHOST_POINTS,
+            score: 0,
+            pellets_eaten: 0,
+            high_score: 0,
+            lives: 0,
+            level: 1,
+            ticks: 0,
+            seed: 0,
+            rng: Rng::new(0),
+            recording: None,
+            playback: None,
+            state: GameState::Ready,
+            state_ticks: 0,
+            screen: None,
+            audio: Audio::new(),
+            settings: Settings::default(),
+            options: None,
+            initials: None,
+            frame_timer: FrameTimer::new(),
+            show_frame_time: false,
+            frame_rate: FrameRate::Capped,
+            previous: HashMap::new()
+        };
+
+        game.subscribe(Game::award_points);
+        game.subscribe(Game::apply_power);
+        game.subscribe(Game::remove_fruit);
+        game.subscribe(Game::advance_state);
+        house::subscribe(&mut game);
+        audio::subscribe(&mut game);
+
+        game
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
 
+        if let Some(screen) = &mut self.screen {
+            screen.set_title(title);
         }
     }
 
-    pub fn add_entity(&mut self, entity: Box<dyn Entity>) {
-        self.entity_enviroment.borrow_mut().add(entity);
+    pub fn state(&self) -> GameState {
+        self.state
     }
 
-    fn start(&mut self) {
-        for ent in self.entity_enviroment.borrow_mut().get_mut() {
-            ent.start(self.entity_enviroment.clone());
+    pub fn set_state(&mut self, state: GameState) {
+        self.state = state;
+        self.state_ticks = 0;
+
+        if state != GameState::Playing {
+            self.audio.set_loop(None);
         }
     }
 
+    pub fn audio(&mut self) -> &mut Audio {
+        &mut self.audio
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn set_frame_rate(&mut self, frame_rate: FrameRate) {
+        self.frame_rate = frame_rate;
+    }
+
+    pub fn add_entity(&mut self, entity: Box<dyn Entity>) -> EntityId {
+        self.entity_enviroment.add(entity)
+    }
+
+    pub(crate) fn start(&mut self) {
+        let world = World::capture(&self.entity_enviroment);
+
+        for (_, ent) in self.entity_enviroment.iter_mut() {
+            ent.start(&world, &mut self.commands);
+        }
+
+        self.apply_commands();
+    }
+
     fn update(&mut self) {
-        let env = self.entity_enviroment.clone();
+        let world = World::capture(&self.entity_enviroment);
 
-        for ent in self.entity_enviroment.borrow_mut().get_mut() {
-            ent.update(env.clone());
+        for (_, ent) in self.entity_enviroment.iter_mut() {
+            ent.update(&world, &mut self.commands);
         }
-    } 
 
-    fn draw(&mut self, canvas: &mut Canvas<Window>) -> io::Result<()> {
-        let texture_creator = canvas.texture_creator();
+        self.remove_dead();
+    }
+
+    fn remove_dead(&mut self) {
+        for (id, ent) in self.entity_enviroment.iter() {
+            if !ent.is_alive() {
+                self.commands.despawn(id);
+            }
+        }
+    }
+
+    // Systems, event handlers and entities all change the world and raise events through `commands`, applied here in order.
+    fn apply_commands(&mut self) {
+        while let Some(command) = self.commands.pop() {
+            match command {
+                Command::Spawn(mut entity) => {
+                    let world = World::capture(&self.entity_enviroment);
+                    entity.start(&world, &mut self.commands);
+                    self.entity_enviroment.add(entity);
+                }
+
+                Command::Despawn(id) => {
+                    self.entity_enviroment.remove(id);
+                }
+
+                Command::Send(id, message) => {
+                    if let Some(ent) = self.entity_enviroment.get_mut(id) {
+                        ent.on_message(&message);
+                    }
+                }
+
+                Command::Broadcast(message) => self.send_message(&message),
+                Command::Publish(event) => self.publish(event)
+            }
+        }
+    }
+
+    fn positions(&self) -> HashMap<EntityId, (i32, i32)> {
+        self.entity_enviroment.query::<Position>().collect()
+    }
+
+    fn draw(&mut self, canvas: &mut Canvas<Window>, textures: &mut Textures, alpha: f64) -> io::Result<()> {
+        let flash = self.state == GameState::LevelClear && (self.state_ticks / LEVEL_CLEAR_FLASH) % 2 == 1;
         
-        for ent in self.entity_enviroment.borrow_mut().get_mut() {
-            let (x, y) = ent.get_pos();
-            let color = ent.get_color().clone();
-            let graphics = ent.get_graphics();
-            
+        let env = &self.entity_enviroment;
+
+        for (id, (graphics, color)) in env.query::<Sprite>() {
+            let (Some(tag), Some(pos)) = (env.component::<Tag>(id), env.component::<Position>(id)) else {
+                continue;
+            };
+
+            if !is_visible(self.state, tag) {
+                continue;
+            }
+
+            let (x, y) = interpolate(self.previous.get(&id).copied(), pos, alpha);
+            let color = if flash && tag == Tag::Block { Color::WHITE } else { color };
+
             canvas.set_draw_color(color);
 
             match graphics {
@@ -84,48 +325,449 @@ impl Game {
                 }
 
                 Graphics::Image { path } => {
-                    let texture = texture_creator.load_texture(path)
-                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't create a texture"))?;
+                    let texture = textures.get(path)?;
                     
                     let dest_rect = Rect::new(x, y, 32, 32);
-                    canvas.copy(&texture, None, Some(dest_rect))
+                    canvas.copy(texture, None, Some(dest_rect))
                         .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw an image"))?;
                 }
+
+                Graphics::Sprite(animation) => {
+                    let Some(frame) = animation.frame() else {
+                        continue;
+                    };
+
+                    let texture = textures.get(&animation.sheet)?;
+
+                    let dest_rect = Rect::new(x, y, 32, 32);
+                    canvas.copy(texture, Some(frame), Some(dest_rect))
+                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw a sprite"))?;
+                }
             }
         }
 
         Ok(())
     }
 
-    fn on_key_down(&mut self, keycode: Keycode) {
-        for ent in self.entity_enviroment.borrow_mut().get_mut() {
-            ent.on_key_down(keycode);
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    pub fn start_recording(&mut self, replay: Replay) {
+        self.recording = Some(replay);
+    }
+
+    pub fn take_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    pub fn start_playback(&mut self, replay: Replay) {
+        self.playback = Some(Playback::new(replay));
+    }
+
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.playback.as_ref().and_then(|playback| playback.divergence())
+    }
+
+    pub(crate) fn on_key_down(&mut self, keycode: Keycode) -> Option<GameState> {
+        if let Some(options) = &mut self.options
+            && options.is_capturing() {
+            options.capture(keycode, &mut self.settings);
+            return None;
+        }
+
+        let action = self.settings.bindings.action(keycode)?;
+        self.on_action(action)
+    }
+
+    pub(crate) fn on_action(&mut self, action: Action) -> Option<GameState> {
+        if let Some(options) = &mut self.options {
+            if options.on_action(action, &mut self.settings) {
+                self.close_options();
+            }
+
+            self.apply_settings();
+            return None;
+        }
+
+        if self.state == GameState::Initials
+            && let Some(entry) = &mut self.initials
+            && entry.on_action(action) {
+            return entry.is_finished().then_some(GameState::Title);
+        }
+
+        match (self.state, action) {
+            (_, Action::Quit) => return Some(GameState::Close),
+
+            (_, Action::FrameTime) => {
+                self.show_frame_time = !self.show_frame_time;
+                return None;
+            }
+
+            (_, Action::Mute) => {
+                self.settings.muted = !self.settings.muted;
+                self.apply_settings();
+                return None;
+            }
+
+            (_, Action::VolumeDown) => {
+                self.settings.volume = self.settings.volume.saturating_sub(1);
+                self.apply_settings();
+                return None;
+            }
+
+            (_, Action::VolumeUp) => {
+                self.settings.volume = (self.settings.volume + 1).min(audio::MAX_VOLUME);
+                self.apply_settings();
+                return None;
+            }
+
+            (GameState::Title, Action::Confirm) => {
+                self.set_state(GameState::Ready);
+                return None;
+            }
+
+            (GameState::Title | GameState::Paused, Action::Options) => {
+                self.options = Some(OptionsMenu::new(self.state));
+                self.set_state(GameState::Options);
+                return None;
+            }
+
+            (GameState::Playing, Action::Pause) => {
+                self.set_state(GameState::Paused);
+                return None;
+            }
+
+            (GameState::Paused, Action::Pause) => {
+                self.set_state(GameState::Playing);
+                return None;
+            }
+
+            (GameState::Title | GameState::Paused | GameState::Initials, _) => return None,
+
+            _ => {}
+        }
+
+        if self.playback.is_some() || action.direction().is_none() {
+            return None;
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.record_input(self.ticks, action);
+        }
+
+        self.send_action(action);
+        None
+    }
+
+    fn send_action(&mut self, action: Action) {
+        for (_, ent) in self.entity_enviroment.iter_mut() {
+            ent.on_action(action);
+        }
+    }
+
+    fn close_options(&mut self) {
+        if let Some(options) = self.options.take() {
+            self.set_state(options.back_to());
+        }
+
+        self.save_settings();
+    }
+
+    pub fn enter_initials(&mut self) {
+        self.initials = Some(InitialsEntry::new(self.score));
+        self.set_state(GameState::Initials);
+    }
+
+    pub fn take_initials(&mut self) -> Option<String> {
+        self.initials
+            .take()
+            .filter(|entry| entry.is_done())
+            .map(|entry| entry.letters())
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.apply_settings();
+    }
+
+    fn apply_settings(&mut self) {
+        self.audio.set_volume(self.settings.volume);
+        self.audio.set_muted(self.settings.muted);
+    }
+
+    pub fn save_settings(&self) {
+        if let Err(e) = self.settings.save() {
+            eprintln!("Can't save settings: {}", e);
         }
     }
     
     pub fn set_scores(&mut self, scores: Vec<(i32, i32)>) {
         self.scores = scores;
+        self.pellet_total = self.pellets_left();
     }
 
-    fn score_logic(&mut self) {
-        let env = self.entity_enviroment.borrow_mut();
-        let pacman = env.get_first("pacman");
+    pub fn set_power_pellets(&mut self, power_pellets: Vec<(i32, i32)>) {
+        self.power_pellets = power_pellets;
+        self.pellet_total = self.pellets_left();
+    }
 
-        if let Some(pacman) = pacman {
-            let (x, y) = pacman.get_pos();
-            let x = x as f64;
-            let y = y as f64;
+    fn pellets_left(&self) -> usize {
+        self.scores.len() + self.power_pellets.len()
+    }
 
-            let round_x = (x / 32.0).round() as i32;
-            let round_y = (y / 32.0).round() as i32;
+    pub fn set_frightened_ticks(&mut self, ticks: u32) {
+        self.frightened_ticks = ticks;
+    }
 
-            for (i, (score_x, score_y)) in self.scores.iter().enumerate() {
-                if (round_x == score_x / 32) && (round_y == score_y / 32) {
-                    self.scores.remove(i);
+    pub fn set_maze(&mut self, maze: Rc<Maze>) {
+        self.maze = maze;
 
-                    break;
-                }
+        if self.bot.is_some() {
+            self.set_autopilot(true);
+        }
+    }
+
+    pub fn set_autopilot(&mut self, enabled: bool) {
+        self.bot = enabled.then(|| Bot::new(self.maze.clone()));
+
+        // The computer's games aren't worth keeping, and saving one would replace the player's last recording.
+        if enabled {
+            self.recording = None;
+        }
+    }
+
+    pub fn is_demo(&self) -> bool {
+        self.demo
+    }
+
+    fn start_demo(&mut self) {
+        self.demo = true;
+        self.set_autopilot(true);
+        self.set_state(GameState::Ready);
+    }
+
+    fn autopilot(&mut self) {
+        let Some(bot) = &self.bot else {
+            return;
+        };
+
+        let world = World::capture(&self.entity_enviroment);
+        let pellets: Vec<(i32, i32)> = self.scores.iter().chain(self.power_pellets.iter()).copied().collect();
+
+        if let Some(action) = bot.decide(&world, &pellets) {
+            self.on_action(action);
+        }
+    }
+
+    pub fn set_house(&mut self, house: GhostHouse) {
+        self.house = house;
+    }
+
+    pub fn house(&mut self) -> &mut GhostHouse {
+        &mut self.house
+    }
+
+    pub fn set_fruit(&mut self, spawn: Option<(i32, i32)>, fruit: FruitKind) {
+        self.fruit_spawn = spawn;
+        self.fruit = fruit;
+        self.fruits_spawned = 0;
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn set_score(&mut self, score: u32) {
+        self.score = score;
+    }
+
+    pub fn pellets_eaten(&self) -> u32 {
+        self.pellets_eaten
+    }
+
+    pub fn set_pellets_eaten(&mut self, pellets_eaten: u32) {
+        self.pellets_eaten = pellets_eaten;
+    }
+
+    pub fn high_score(&self) -> u32 {
+        self.high_score.max(self.score)
+    }
+
+    pub fn set_high_score(&mut self, high_score: u32) {
+        self.high_score = high_score;
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn set_lives(&mut self, lives: u32) {
+        self.lives = lives;
+    }
+
+    pub fn set_level(&mut self, level: usize) {
+        self.level = level;
+    }
+
+    fn draw_hud(&mut self, canvas: &mut Canvas<Window>, textures: &mut Textures) -> io::Result<()> {
+        let hud = Hud {
+            score: self.score,
+            high_score: self.high_score(),
+            lives: self.lives,
+            level: self.level,
+            fruits: (self.level.saturating_sub(HUD_FRUITS)..self.level).map(FruitKind::for_level).collect()
+        };
+
+        hud.draw(canvas, textures, self.width, self.height)
+    }
+
+    fn eat_pellets(&mut self) {
+        let pacman = self.entity_enviroment.query_tagged::<Position>(Tag::Pacman).next();
+        let Some((_, (x, y))) = pacman else {
+            return;
+        };
+
+        let round_x = (x as f64 / 32.0).round() as i32;
+        let round_y = (y as f64 / 32.0).round() as i32;
+        let eaten = |(pellet_x, pellet_y): &(i32, i32)| round_x == pellet_x / 32 && round_y == pellet_y / 32;
+
+        if let Some(i) = self.scores.iter().position(eaten) {
+            let pos = self.scores.remove(i);
+            self.publish(GameEvent::PelletEaten { pos });
+        }
+
+        if let Some(i) = self.power_pellets.iter().position(eaten) {
+            let pos = self.power_pellets.remove(i);
+            self.publish(GameEvent::PowerPelletEaten { pos });
+        }
+    }
+
+    fn release_ghosts(&mut self) {
+        if let Some(id) = self.house.tick() {
+            self.commands.send(id, Message::Release);
+        }
+    }
+
+    fn spawn_fruit(&mut self) {
+        let Some((x, y)) = self.fruit_spawn else {
+            return;
+        };
+
+        let eaten = self.pellet_total - self.pellets_left();
+        if FRUIT_PELLETS.get(self.fruits_spawned).is_none_or(|pellets| eaten < *pellets) {
+            return;
+        }
+
+        self.fruits_spawned += 1;
+        self.commands.spawn(Box::new(Fruit::new(self.fruit, x, y)));
+        self.commands.publish(GameEvent::FruitSpawned { pos: (x, y) });
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.publish(event);
+    }
+
+    pub fn subscribe(&mut self, handler: Handler) {
+        self.events.subscribe(handler);
+    }
+
+    fn dispatch_events(&mut self) {
+        while let Some(event) = self.events.pop() {
+            let handlers = self.events.handlers().to_vec();
+
+            for handler in handlers {
+                handler(self, &event);
+            }
+
+            for (_, ent) in self.entity_enviroment.iter_mut() {
+                ent.on_event(&event);
+            }
+
+            self.apply_commands();
+        }
+    }
+
+    fn award_points(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PelletEaten { .. } => {
+                self.score += PELLET_POINTS;
+                self.pellets_eaten += 1;
+            }
+
+            GameEvent::PowerPelletEaten { .. } => {
+                self.score += POWER_PELLET_POINTS;
+                self.pellets_eaten += 1;
+                self.ghost_points = GHOST_POINTS;
+            }
+
+            GameEvent::GhostEaten { .. } => {
+                self.score += self.ghost_points;
+                self.ghost_points = (self.ghost_points * 2).min(GHOST_POINTS_MAX);
+            }
+
+            GameEvent::FruitEaten { kind, .. } => self.score += kind.points(),
+
+            _ => {}
+        }
+    }
+
+    fn apply_power(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PowerPelletEaten { .. } => self.commands.broadcast(Message::Frighten(self.frightened_ticks)),
+            GameEvent::GhostEaten { id } => self.commands.send(*id, Message::Eaten),
+
+            _ => {}
+        }
+    }
+
+    fn background_loop(&self) -> Loop {
+        let ghosts = || self.entity_enviroment.query_tagged::<GhostStatus>(Tag::Ghost);
+
+        if ghosts().any(|(_, status)| status.returning) {
+            Loop::Eyes
+        } else if ghosts().any(|(_, status)| status.frightened) {
+            Loop::Frightened
+        } else {
+            Loop::siren(self.pellets_left(), self.pellet_total)
+        }
+    }
+
+    fn remove_fruit(&mut self, event: &GameEvent) {
+        if let GameEvent::FruitEaten { id, .. } = event {
+            self.commands.despawn(*id);
+        }
+    }
+
+    fn advance_state(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PacmanDied => {
+                self.lives = self.lives.saturating_sub(1);
+                self.set_state(GameState::Dying);
             }
+
+            GameEvent::LevelCleared => self.set_state(GameState::LevelClear),
+
+            _ => {}
+        }
+    }
+
+    fn send_message(&mut self, message: &Message) {
+        for (_, ent) in self.entity_enviroment.iter_mut() {
+            ent.on_message(message);
         }
     }
 
@@ -139,106 +781,420 @@ impl Game {
                 .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw score"))?;
         }
 
+        for pos in self.power_pellets.iter() {
+            let dis = 32 / 2 - 16 / 2;
+
+            canvas.fill_rect(Rect::new(pos.0 + dis, pos.1 + dis, 16, 16))
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw power pellet"))?;
+        }
+
         Ok(())
     }
 
     pub fn clear(&mut self) {
-        let mut env = self.entity_enviroment.borrow_mut();
-        env.get_mut().clear();
+        self.entity_enviroment.clear();
+        self.events.clear();
+        self.commands = Commands::new();
 
         self.scores.clear();
+        self.power_pellets.clear();
+        self.bot = None;
+        self.demo = false;
+        self.initials = None;
+        self.ticks = 0;
+        self.set_state(GameState::Ready);
     }
 
-    fn update_ghost(&mut self) -> bool {
-        let mut env = self.entity_enviroment.borrow_mut();
-        
-        let pacman = env.get_first("pacman");
-        if let Some(pacman) = pacman {
-            let (x, y) = pacman.get_pos();
-            let x = x as f64;
-            let y = y as f64;
-            let round_x = (x / 32.0).round() as i32;
-            let round_y = (y / 32.0).round() as i32;
-
-            for ent in env.get_mut() {
-                if ent.get_name() == "ghost" {
-                    let (ghost_x, ghost_y) = ent.get_pos();
-                    let ghost_x = ghost_x as f64;
-                    let ghost_y = ghost_y as f64;
-                    let round_ghost_x = (ghost_x / 32.0).round() as i32;
-                    let round_ghost_y = (ghost_y / 32.0).round() as i32;
-
-                    if (round_x == round_ghost_x) && (round_y == round_ghost_y) {
-                        return true;
-                    }
+    pub fn world_state(&self) -> WorldState {
+        let env = &self.entity_enviroment;
+        let pacman = env.query_tagged::<Position>(Tag::Pacman).next().map(|(_, pos)| pos);
+        let ghosts = env.query_tagged::<Position>(Tag::Ghost).map(|(_, pos)| pos).collect();
+
+        WorldState {
+            pacman,
+            ghosts,
+            pellets: self.scores.clone(),
+            power_pellets: self.power_pellets.clone(),
+            score: self.score,
+            lives: self.lives,
+            ticks: self.ticks
+        }
+    }
+
+    fn checksum(&self) -> u64 {
+        let env = &self.entity_enviroment;
+        let mut values = Vec::new();
+
+        for (_, (x, y)) in env.query::<Position>() {
+            values.push(x as i64);
+            values.push(y as i64);
+        }
+
+        values.push(self.scores.len() as i64);
+        values.push(self.power_pellets.len() as i64);
+        values.push(self.score as i64);
+        values.push(self.lives as i64);
+
+        replay::checksum(&values)
+    }
+
+    pub(crate) fn tick(&mut self) -> Option<GameState> {
+        self.previous = self.positions();
+
+        if self.state == GameState::Title {
+            self.state_ticks += 1;
+
+            if self.state_ticks >= ATTRACT_TICKS {
+                self.start_demo();
+            }
+
+            return None;
+        }
+
+        if matches!(self.state, GameState::Paused | GameState::Options | GameState::Initials) {
+            return None;
+        }
+
+        if let Some(playback) = &mut self.playback {
+            if playback.finished(self.ticks) {
+                return Some(GameState::Close);
+            }
+
+            for action in playback.inputs_at(self.ticks) {
+                self.send_action(action);
+            }
+        }
+
+        if self.state == GameState::Playing {
+            self.autopilot();
+        }
+
+        self.ticks += 1;
+
+        self.step();
+        let checksum = self.checksum();
+
+        if let Some(recording) = &mut self.recording {
+            recording.record_checksum(self.ticks, checksum);
+        }
+
+        if let Some(playback) = &mut self.playback {
+            playback.check(self.ticks, checksum);
+        }
+
+        if self.state.is_finished() {
+            return Some(if self.demo { GameState::Title } else { self.state });
+        }
+
+        None
+    }
+
+    fn step(&mut self) {
+        self.state_ticks += 1;
+
+        if self.ticks == 1 && !self.demo {
+            self.audio.play(Sound::Intro);
+        }
+
+        match self.state {
+            GameState::Ready if self.state_ticks >= READY_TICKS => self.set_state(GameState::Playing),
+
+            GameState::Playing => self.play(),
+
+            GameState::Dying if self.state_ticks >= DEATH_TICKS => {
+                if self.lives == 0 {
+                    self.set_state(GameState::GameOver);
+                } else {
+                    self.commands.broadcast(Message::Reset);
+                    self.set_state(GameState::Ready);
                 }
             }
+
+            GameState::LevelClear if self.state_ticks >= LEVEL_CLEAR_TICKS => self.set_state(GameState::Win),
+
+            GameState::GameOver if self.state_ticks >= GAME_OVER_TICKS => self.set_state(GameState::Lose),
+
+            _ => {}
         }
-        
-        false
+
+        self.apply_commands();
     }
 
-    pub fn run(&mut self) -> io::Result<GameState> {
-        let sdl_context = sdl2::init().unwrap();
-        let video_subsystem = sdl_context.video().unwrap();
-
-        let window = video_subsystem.window(&self.title, self.width, self.height)
-            .position_centered()
-            .build()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
-
-        let mut canvas = window
-            .into_canvas()
-            .build()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
-        
-        let _image_context = sdl2::image::init(InitFlag::JPG)
-            .map(|_| io::Error::new(io::ErrorKind::InvalidData, "Image failed"));
+    fn play(&mut self) {
+        for system in PLAY_SYSTEMS {
+            system(self);
+            self.apply_commands();
+            self.dispatch_events();
+
+            if self.state != GameState::Playing {
+                return;
+            }
+        }
+
+        if !self.demo {
+            let track = self.background_loop();
+            self.audio.set_loop(Some(track));
+        }
+    }
+
+    fn check_level_clear(&mut self) {
+        if self.scores.is_empty() && self.power_pellets.is_empty() {
+            self.publish(GameEvent::LevelCleared);
+        }
+    }
+
+    fn collide(&mut self) {
+        let env = &self.entity_enviroment;
+
+        let Some((_, pacman)) = env.first(Tag::Pacman) else {
+            return;
+        };
+
+        let pacman_pos = pacman.get_pos();
+        let Some(pacman_collider) = pacman.get_collider() else {
+            return;
+        };
+
+        let touches = |tag: Tag| -> Vec<EntityId> {
+            env.tagged(tag)
+                .filter(|(_, other)| {
+                    other.get_collider().is_some_and(|collider| pacman_collider.overlaps(pacman_pos, &collider, other.get_pos()))
+                })
+                .map(|(id, _)| id)
+                .collect()
+        };
+
+        for id in touches(Tag::Fruit) {
+            self.events.publish(GameEvent::FruitEaten { id, kind: self.fruit });
+        }
+
+        for id in touches(Tag::Ghost) {
+            let Some(status) = env.component::<GhostStatus>(id) else {
+                continue;
+            };
+
+            if !status.frightened {
+                self.events.publish(GameEvent::PacmanDied);
+                return;
+            }
+
+            self.events.publish(GameEvent::GhostEaten { id });
+        }
+    }
 
+    fn draw_banner(&self, canvas: &mut Canvas<Window>, text: &str, color: Color) -> io::Result<()> {
+        let width = font::text_width(text, BANNER_SCALE);
+        let height = font::GLYPH_HEIGHT * BANNER_SCALE;
+        let x = (self.width as i32 - width) / 2;
+        let y = (self.height as i32 - height) / 2;
+
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.fill_rect(Rect::new(x - 8, y - 8, (width + 16) as u32, (height + 16) as u32))
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw banner"))?;
+
+        font::draw_text(canvas, text, x, y, BANNER_SCALE, color)
+    }
+
+    fn draw_death(&self, canvas: &mut Canvas<Window>) -> io::Result<()> {
+        let env = &self.entity_enviroment;
+        let Some((id, _)) = env.first(Tag::Pacman) else {
+            return Ok(());
+        };
+
+        let (Some((x, y)), Some(dir)) = (env.component::<Position>(id), env.component::<Velocity>(id)) else {
+            return Ok(());
+        };
+
+        let facing = match dir {
+            (0, 1) => 90,
+            (-1, 0) => 180,
+            (0, -1) => 270,
+            _ => 0
+        };
+
+        let mouth = 30 + (150 * self.state_ticks / DEATH_TICKS) as i16;
+        if mouth >= 180 {
+            return Ok(());
+        }
+
+        canvas.filled_pie((x + 16) as i16, (y + 16) as i16, 14, facing + mouth, facing + 360 - mouth, Color::YELLOW)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn draw_title(&self, canvas: &mut Canvas<Window>) -> io::Result<()> {
+        let center = self.width as i32 / 2;
+        let key = |action| options::key_name(&self.settings, action);
+        let lines = [
+            ("PACMAN".to_string(), TITLE_SCALE, Color::YELLOW, 180),
+            (format!("PRESS {} TO START", key(Action::Confirm)), BANNER_SCALE, Color::WHITE, 340),
+            (format!("{} OPTIONS - {} PAUSE - {} QUIT", key(Action::Options), key(Action::Pause), key(Action::Quit)), 2, Color::GREY, 420),
+        ];
+
+        for (text, scale, color, y) in lines {
+            font::draw_text(canvas, &text, center - font::text_width(&text, scale) / 2, y, scale, color)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_frame_time(&self, canvas: &mut Canvas<Window>) -> io::Result<()> {
+        if !self.show_frame_time {
+            return Ok(());
+        }
+
+        let text = format!("{:.2} MS", self.frame_timer.average().as_secs_f64() * 1000.0);
+        let x = self.width as i32 - 16 - font::text_width(&text, 2);
+
+        font::draw_text(canvas, &text, x, 16, 2, Color::GREEN)
+    }
+
+    fn draw_frame(&mut self, canvas: &mut Canvas<Window>, textures: &mut Textures, alpha: f64) -> io::Result<()> {
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
         canvas.clear();
-        canvas.present();
-        
-        let mut event_pump = sdl_context
-            .event_pump()
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.draw_frame_time(canvas)?;
 
+        if self.state == GameState::Title {
+            self.draw_hud(canvas, textures)?;
+            return self.draw_title(canvas);
+        }
+
+        if let Some(options) = &self.options {
+            return options.draw(canvas, &self.settings, self.width, self.height);
+        }
+
+        if let Some(entry) = &self.initials {
+            return entry.draw(canvas, &self.settings, self.width, self.height);
+        }
+
+        self.draw_score(canvas)?;
+        self.draw(canvas, textures, alpha)?;
+        self.draw_hud(canvas, textures)?;
+
+        if self.demo {
+            let text = "DEMO - PRESS ANY KEY";
+            font::draw_text(canvas, text, (self.width as i32 - font::text_width(text, 2)) / 2, 16, 2, Color::GREY)?;
+        }
+
+        match self.state {
+            GameState::Ready => self.draw_banner(canvas, "READY!", Color::YELLOW),
+            GameState::Paused => self.draw_banner(canvas, "PAUSED", Color::WHITE),
+            GameState::GameOver => self.draw_banner(canvas, "GAME OVER", Color::RED),
+            GameState::Dying => self.draw_death(canvas),
+            _ => Ok(())
+        }
+    }
+
+    fn take_screen(&mut self) -> io::Result<Screen> {
+        match self.screen.take() {
+            Some(screen) => Ok(screen),
+            None => {
+                let screen = Screen::open(&self.title, self.width, self.height, self.frame_rate, self.settings.display())?;
+                self.audio.open(screen.sdl());
+
+                Ok(screen)
+            }
+        }
+    }
+
+    fn restore_screen(&mut self, screen: Screen) {
+        self.screen = Some(screen);
+    }
+
+    pub fn run(&mut self) -> io::Result<GameState> {
+        let mut screen = self.take_screen()?;
+        let result = self.run_scenes(&mut screen);
+        self.restore_screen(screen);
+
+        result
+    }
+
+    fn run_scenes(&mut self, screen: &mut Screen) -> io::Result<GameState> {
+        let texture_creator = screen.canvas.texture_creator();
+        let mut textures = Textures::new(&texture_creator);
+        textures.preload(&assets::ALL)?;
 
         self.start();
 
+        let mut last_frame = Instant::now();
+        let mut lag = Duration::ZERO;
+
         loop {
-            canvas.set_draw_color(Color::RGB(0, 0, 0));
-            canvas.clear();
-            for event in event_pump.poll_iter() {
+            let frame_start = Instant::now();
+            lag += (frame_start - last_frame).min(MAX_FRAME_TIME);
+            last_frame = frame_start;
+
+            self.frame_timer.begin();
+
+            for event in screen.event_pump.poll_iter() {
                 match event {
-                    Event::Quit {..} |
-                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    Event::Quit {..} => {
                         return Ok(GameState::Close)
                     },
-                    Event::KeyDown { keycode, .. } => {
-                        if let Some(keycode) = keycode {
-                            self.on_key_down(keycode);
+                    Event::KeyDown { .. } if self.demo => {
+                        return Ok(GameState::Title)
+                    },
+                    Event::KeyDown { keycode: Some(keycode), .. } => {
+                        if let Some(state) = self.on_key_down(keycode) {
+                            return Ok(state);
                         }
                     },
-                    _ => {}
+                    event => {
+                        let Some(action) = screen.gamepads.on_event(&event) else {
+                            continue;
+                        };
+
+                        if self.demo {
+                            return Ok(GameState::Title);
+                        }
+
+                        if let Some(state) = self.on_action(action) {
+                            return Ok(state);
+                        }
+                    }
                 }
             }
-            
-            if self.update_ghost() {
-                return Ok(GameState::Lose); 
-            }
 
-            self.update();
-            self.score_logic();
-            
-            if self.scores.len() == 0 {
-                return Ok(GameState::Win);
+            screen.set_display(self.settings.display());
+
+            while lag >= TICK_DURATION {
+                if let Some(state) = self.tick() {
+                    return Ok(state);
+                }
+
+                lag -= TICK_DURATION;
             }
 
-            self.draw_score(&mut canvas)?;
-            self.draw(&mut canvas)?;
+            let alpha = lag.as_secs_f64() / TICK_DURATION.as_secs_f64();
+            self.draw_frame(&mut screen.canvas, &mut textures, alpha)?;
+
+            screen.canvas.present();
+            self.frame_timer.end();
 
-            canvas.present();
-            thread::sleep(Duration::new(0, 1_000_000_000u32 / 60));
+            if self.frame_rate == FrameRate::Capped {
+                let next_tick = frame_start + (TICK_DURATION - lag);
+                thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+            }
         }
     }
 }
+
+fn is_visible(state: GameState, tag: Tag) -> bool {
+    match state {
+        GameState::Dying => tag == Tag::Block,
+        GameState::LevelClear => tag != Tag::Ghost,
+        _ => true
+    }
+}
+
+fn interpolate(previous: Option<(i32, i32)>, (x, y): (i32, i32), alpha: f64) -> (i32, i32) {
+    let Some((previous_x, previous_y)) = previous else {
+        return (x, y);
+    };
+
+    if (x - previous_x).abs() > BLOCK_SIZE || (y - previous_y).abs() > BLOCK_SIZE {
+        return (x, y);
+    }
+
+    let blend = |previous: i32, current: i32| previous + ((current - previous) as f64 * alpha).round() as i32;
+    (blend(previous_x, x), blend(previous_y, y))
+}
@@ -0,0 +1,116 @@
+use sdl2::{GameControllerSubsystem, Sdl};
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+
+use crate::input::Action;
+
+const DEADZONE: i32 = 8000;
+
+struct Pad {
+    controller: GameController,
+    stick: (i16, i16),
+    held: Option<Action>
+}
+
+impl Pad {
+    fn steer(&mut self) -> Option<Action> {
+        let (x, y) = (self.stick.0 as i32, self.stick.1 as i32);
+
+        let action = if x.abs().max(y.abs()) < DEADZONE {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x < 0 { Action::MoveLeft } else { Action::MoveRight })
+        } else {
+            Some(if y < 0 { Action::MoveUp } else { Action::MoveDown })
+        };
+
+        if action == self.held {
+            return None;
+        }
+
+        self.held = action;
+        action
+    }
+}
+
+pub struct Gamepads {
+    subsystem: Option<GameControllerSubsystem>,
+    pads: Vec<Pad>
+}
+
+fn button_action(button: Button) -> Option<Action> {
+    match button {
+        Button::DPadUp => Some(Action::MoveUp),
+        Button::DPadDown => Some(Action::MoveDown),
+        Button::DPadLeft => Some(Action::MoveLeft),
+        Button::DPadRight => Some(Action::MoveRight),
+        Button::A => Some(Action::Confirm),
+        Button::Start => Some(Action::Pause),
+        Button::Back => Some(Action::Options),
+        _ => None
+    }
+}
+
+impl Gamepads {
+    pub fn open(sdl: &Sdl) -> Gamepads {
+        let subsystem = sdl.game_controller()
+            .map_err(|e| eprintln!("Game controllers disabled: {}", e))
+            .ok();
+
+        Gamepads {
+            subsystem,
+            pads: Vec::new()
+        }
+    }
+
+    fn pad_mut(&mut self, which: u32) -> Option<&mut Pad> {
+        self.pads.iter_mut().find(|pad| pad.controller.instance_id() == which)
+    }
+
+    pub fn on_event(&mut self, event: &Event) -> Option<Action> {
+        match event {
+            // SDL also sends this for every controller already plugged in at startup.
+            Event::ControllerDeviceAdded { which, .. } => {
+                let subsystem = self.subsystem.as_ref()?;
+
+                match subsystem.open(*which) {
+                    Ok(controller) => {
+                        let id = controller.instance_id();
+
+                        if self.pad_mut(id).is_none() {
+                            self.pads.push(Pad { controller, stick: (0, 0), held: None });
+                        }
+                    }
+
+                    Err(e) => eprintln!("Can't open game controller {}: {}", which, e)
+                }
+
+                None
+            }
+
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.pads.retain(|pad| pad.controller.instance_id() != *which);
+                None
+            }
+
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.pad_mut(*which)?;
+                button_action(*button)
+            }
+
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                let pad = self.pad_mut(*which)?;
+
+                match axis {
+                    Axis::LeftX => pad.stick.0 = *value,
+                    Axis::LeftY => pad.stick.1 = *value,
+                    _ => return None
+                }
+
+                pad.steer()
+            }
+
+            _ => None
+        }
+    }
+}
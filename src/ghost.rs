@@ -1,92 +1,507 @@
+use std::any::{Any, TypeId};
 use std::rc::Rc;
-use std::cell::RefCell;
 
-use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 
-use crate::entity::{Entity, EntityEnviroment};
-use crate::graphics::Graphics;
+use crate::assets;
+use crate::entity::{Collider, Entity, EntityId, GhostStatus, Message, Tag};
+use crate::game::TICKS_PER_SECOND;
+use crate::graphics::{self, Animation, Graphics, LoopMode};
+use crate::input::Action;
+use crate::level::Door;
+use crate::maze::Maze;
+use crate::navigation::{self, Navigator};
+use crate::pathfinding::{DistanceMap, Graph};
+use crate::rng::Rng;
+use crate::speed::Stride;
+use crate::world::{Commands, World};
 
-const GHOST_SPEED: i32 = 5;
+pub const GHOST_SPEED: f64 = 6.0;
+
+const MODE_SCHEDULE: [(GhostMode, u32); 7] = [
+    (GhostMode::Scatter, 7),
+    (GhostMode::Chase, 20),
+    (GhostMode::Scatter, 7),
+    (GhostMode::Chase, 20),
+    (GhostMode::Scatter, 5),
+    (GhostMode::Chase, 20),
+    (GhostMode::Scatter, 5),
+];
+
+const FLASH_TICKS: u32 = 2 * TICKS_PER_SECOND;
+const FLASH_PERIOD: u32 = 12;
+const SKIRT_FRAMES: i32 = 2;
+const SKIRT_TICKS: u32 = 8;
+const GHOST_COLLIDER: Collider = Collider { half_size: 8 };
+
+const HOUSE_SPEED_FACTOR: f64 = 0.5;
+const EYES_SPEED_FACTOR: f64 = 2.0;
+
+const PINKY_AHEAD: i32 = 4;
+const INKY_AHEAD: i32 = 2;
+const CLYDE_DISTANCE: i32 = 8;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Personality {
+    Blinky,
+    Pinky,
+    Inky,
+    Clyde
+}
+
+impl Personality {
+    pub fn convert(name: &str) -> Option<Personality> {
+        match name {
+            "blinky" => Some(Personality::Blinky),
+            "pinky" => Some(Personality::Pinky),
+            "inky" => Some(Personality::Inky),
+            "clyde" => Some(Personality::Clyde),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Personality::Blinky => "blinky",
+            Personality::Pinky => "pinky",
+            Personality::Inky => "inky",
+            Personality::Clyde => "clyde"
+        }
+    }
+
+    pub fn sprite(&self) -> &'static str {
+        match self {
+            Personality::Blinky => assets::BLINKY,
+            Personality::Pinky => assets::PINKY,
+            Personality::Inky => assets::INKY,
+            Personality::Clyde => assets::CLYDE
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GhostMode {
+    Scatter,
+    Chase,
+    Frightened,
+    Eyes
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum House {
+    Waiting,
+    Leaving,
+    Outside,
+    Entering
+}
 
 pub struct Ghost {
-    name: String,
     x: i32,
     y: i32,
-    start_x: i32,
-    start_y: i32,
-    goto_x: i32,
-    goto_y: i32,
+    home: (i32, i32),
+    exit: Option<(i32, i32)>,
+    route_out: Option<DistanceMap>,
+    den: (i32, i32),
+    house: House,
     graphics: Graphics,
     color: Color,
-    forward: bool,
+    personality: Personality,
+    scatter: (i32, i32),
+    dir: (i8, i8),
+    mode: GhostMode,
+    status: GhostStatus,
+    phase: usize,
+    phase_ticks: u32,
+    frightened_ticks: u32,
+    speed: f64,
+    stride: Stride,
+    nav: Navigator,
+    partner: Option<EntityId>,
+    rng: Rng
 }
 
 impl Ghost {
-    pub fn create(name: &str, x: i32, y: i32, goto_x: i32, goto_y: i32, graphics: Graphics) -> Ghost {
+    pub fn create(personality: Personality, x: i32, y: i32, scatter: (i32, i32)) -> Ghost {
         Ghost {
-            name: name.to_string(),
             x,
             y,
-            start_x: x,
-            start_y: y,
-            goto_x,
-            goto_y,
-            graphics: graphics,
+            home: (x, y),
+            exit: None,
+            route_out: None,
+            den: (x, y),
+            house: House::Outside,
+            graphics: Graphics::Sprite(Animation::create(personality.sprite(), graphics::frames(0, SKIRT_FRAMES), SKIRT_TICKS, LoopMode::Loop)),
             color: Color::RED,
-            forward: true,
+            personality,
+            scatter,
+            dir: (0, 0),
+            mode: GhostMode::Scatter,
+            status: GhostStatus::default(),
+            phase: 0,
+            phase_ticks: 0,
+            frightened_ticks: 0,
+            speed: GHOST_SPEED,
+            stride: Stride::default(),
+            nav: Navigator::default(),
+            partner: None,
+            rng: Rng::new(0)
+        }
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed;
+    }
+
+    pub fn set_maze(&mut self, maze: Rc<Maze>) {
+        self.nav = Navigator::new(maze);
+    }
+
+    pub fn set_door(&mut self, door: &Door) {
+        let home = self.nav.tile_of(self.home);
+
+        self.exit = Some(door.exit);
+        self.route_out = Some(Graph::new(self.nav.maze(), true).distances(door.exit));
+        self.den = if home == door.exit { self.nav.center_of(door.inside) } else { self.home };
+        self.house = self.starting_house();
+    }
+
+    pub fn set_partner(&mut self, partner: EntityId) {
+        self.partner = Some(partner);
+    }
+
+    pub fn set_rng(&mut self, rng: Rng) {
+        self.rng = rng;
+    }
+
+    pub fn personality(&self) -> Personality {
+        self.personality
+    }
+
+    pub fn mode(&self) -> GhostMode {
+        self.mode
+    }
+
+    pub fn house(&self) -> House {
+        self.house
+    }
+
+    fn starting_house(&self) -> House {
+        match self.exit {
+            Some(exit) if self.nav.tile_of(self.home) != exit => House::Waiting,
+            _ => House::Outside
+        }
+    }
+
+    pub fn frighten(&mut self, ticks: u32) {
+        if self.mode == GhostMode::Eyes {
+            return;
+        }
+
+        if self.mode != GhostMode::Frightened {
+            self.reverse();
+        }
+
+        self.set_mode(GhostMode::Frightened);
+        self.frightened_ticks = ticks;
+    }
+
+    fn set_mode(&mut self, mode: GhostMode) {
+        self.mode = mode;
+        self.status = GhostStatus {
+            frightened: mode == GhostMode::Frightened,
+            returning: mode == GhostMode::Eyes
+        };
+    }
+
+    fn release(&mut self) {
+        if self.house == House::Waiting {
+            self.house = House::Leaving;
+        }
+    }
+
+    fn become_eyes(&mut self) {
+        if self.house == House::Leaving {
+            self.house = House::Entering;
+        }
+
+        self.set_mode(GhostMode::Eyes);
+        self.frightened_ticks = 0;
+    }
+
+    fn reset(&mut self) {
+        (self.x, self.y) = self.home;
+        self.dir = (0, 0);
+        self.house = self.starting_house();
+        self.set_mode(GhostMode::Scatter);
+        self.phase = 0;
+        self.phase_ticks = 0;
+        self.frightened_ticks = 0;
+        self.stride.reset();
+    }
+
+    fn update_graphics(&mut self) {
+        let (sheet, row) = if self.mode == GhostMode::Eyes {
+            (assets::EYES, graphics::direction_row(self.dir))
+        } else if self.mode != GhostMode::Frightened {
+            (self.personality.sprite(), graphics::direction_row(self.dir))
+        } else if self.frightened_ticks < FLASH_TICKS && (self.frightened_ticks / FLASH_PERIOD).is_multiple_of(2) {
+            (assets::FRIGHTENED, 1)
+        } else {
+            (assets::FRIGHTENED, 0)
+        };
+
+        if let Graphics::Sprite(animation) = &mut self.graphics {
+            let count = if self.mode == GhostMode::Eyes { 1 } else { SKIRT_FRAMES };
+            animation.set_frames(sheet, graphics::frames(row, count));
+            animation.step();
         }
     }
+
+    fn reverse(&mut self) {
+        self.dir = navigation::reverse(self.dir);
+    }
+
+    fn scheduled_mode(&self) -> GhostMode {
+        match MODE_SCHEDULE.get(self.phase) {
+            Some((mode, _)) => *mode,
+            None => GhostMode::Chase
+        }
+    }
+
+    fn update_mode(&mut self) {
+        if self.mode == GhostMode::Eyes {
+            return;
+        }
+
+        if self.mode == GhostMode::Frightened {
+            self.frightened_ticks = self.frightened_ticks.saturating_sub(1);
+
+            if self.frightened_ticks == 0 {
+                self.set_mode(self.scheduled_mode());
+            }
+
+            return;
+        }
+
+        let Some((_, seconds)) = MODE_SCHEDULE.get(self.phase) else {
+            return;
+        };
+
+        self.phase_ticks += 1;
+
+        if self.phase_ticks >= seconds * TICKS_PER_SECOND {
+            self.phase += 1;
+            self.phase_ticks = 0;
+            self.set_mode(self.scheduled_mode());
+
+            if self.house == House::Outside {
+                self.reverse();
+            }
+        }
+    }
+
+    fn enter_tile(&mut self, tile: (i32, i32)) {
+        if self.house == House::Outside && self.mode == GhostMode::Eyes && Some(tile) == self.exit {
+            self.house = House::Entering;
+        }
+
+        let inside = self.house == House::Entering || self.exit.is_none();
+        if self.mode == GhostMode::Eyes && inside && tile == self.nav.tile_of(self.den) {
+            self.set_mode(self.scheduled_mode());
+            self.house = if self.exit.is_some() { House::Leaving } else { House::Outside };
+            self.dir = (0, 0);
+        }
+
+        if self.house == House::Leaving && Some(tile) == self.exit {
+            self.house = House::Outside;
+        }
+    }
+
+    fn target(&self, tile: (i32, i32), world: &World) -> (i32, i32) {
+        let den = self.nav.tile_of(self.den);
+
+        match (self.house, self.mode) {
+            (House::Entering, _) => return den,
+            (House::Leaving, _) => return self.exit.unwrap_or(den),
+            (_, GhostMode::Eyes) => return self.exit.unwrap_or(den),
+            _ => {}
+        }
+
+        if self.mode == GhostMode::Scatter {
+            return self.scatter;
+        }
+
+        let Some(pacman) = world.first(Tag::Pacman) else {
+            return self.scatter;
+        };
+
+        let (dir_x, dir_y) = (pacman.dir.0 as i32, pacman.dir.1 as i32);
+        let pacman = self.nav.tile_of(pacman.pos);
+
+        match self.personality {
+            Personality::Blinky => pacman,
+
+            Personality::Pinky => (pacman.0 + dir_x * PINKY_AHEAD, pacman.1 + dir_y * PINKY_AHEAD),
+
+            Personality::Inky => {
+                let blinky = self.partner
+                    .and_then(|id| world.get(id))
+                    .map_or(tile, |blinky| self.nav.tile_of(blinky.pos));
+                let ahead = (pacman.0 + dir_x * INKY_AHEAD, pacman.1 + dir_y * INKY_AHEAD);
+
+                (ahead.0 * 2 - blinky.0, ahead.1 * 2 - blinky.1)
+            }
+
+            Personality::Clyde => {
+                if distance(tile, pacman) > CLYDE_DISTANCE * CLYDE_DISTANCE {
+                    pacman
+                } else {
+                    self.scatter
+                }
+            }
+        }
+    }
+
+    fn choose_dir(&mut self, tile: (i32, i32), world: &World) -> (i8, i8) {
+        self.enter_tile(tile);
+
+        let reverse = navigation::reverse(self.dir);
+        let exits: Vec<(i8, i8)> = self.nav.exits(tile)
+            .into_iter()
+            .filter(|dir| *dir != reverse)
+            .collect();
+
+        if exits.is_empty() {
+            if !self.nav.is_open(tile, reverse) {
+                return (0, 0);
+            }
+
+            return reverse;
+        }
+
+        if self.mode == GhostMode::Frightened && self.house == House::Outside {
+            return exits[self.rng.range(exits.len() as u32) as usize];
+        }
+
+        // Eyes take the shortest way back to the door instead of aiming straight at it.
+        if self.mode == GhostMode::Eyes
+            && self.house == House::Outside
+            && let Some(route) = &self.route_out
+        {
+            let maze = self.nav.maze();
+
+            return exits
+                .iter()
+                .copied()
+                .min_by_key(|dir| route.get(maze.wrap_tile(navigation::neighbour(tile, *dir))).unwrap_or(u32::MAX))
+                .unwrap_or(self.dir);
+        }
+
+        let target = self.target(tile, world);
+        exits
+            .iter()
+            .copied()
+            .min_by_key(|dir| distance(navigation::neighbour(tile, *dir), target))
+            .unwrap_or(self.dir)
+    }
+
+    fn current_speed(&self) -> f64 {
+        let tile = self.nav.tile_of((self.x, self.y));
+
+        if self.mode == GhostMode::Eyes {
+            self.speed * EYES_SPEED_FACTOR
+        } else if self.house == House::Leaving {
+            self.speed * HOUSE_SPEED_FACTOR
+        } else if self.mode == GhostMode::Frightened || self.nav.maze().is_tunnel(tile) {
+            self.speed / 2.0
+        } else {
+            self.speed
+        }
+    }
+
+    fn step(&mut self, world: &World) {
+        if self.house == House::Waiting {
+            return;
+        }
+
+        let doors_open = self.mode == GhostMode::Eyes || matches!(self.house, House::Leaving | House::Entering);
+        self.nav.set_doors_open(doors_open);
+
+        let speed = self.current_speed();
+        let pixels = self.stride.next(speed);
+        let nav = self.nav.clone();
+
+        let (pos, dir) = nav.travel((self.x, self.y), self.dir, pixels, |tile, dir| {
+            self.dir = dir;
+            self.choose_dir(tile, world)
+        });
+
+        (self.x, self.y) = pos;
+        self.dir = dir;
+    }
+}
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
+    let dx = a.0 - b.0;
+    let dy = a.1 - b.1;
+
+    dx * dx + dy * dy
 }
 
 impl Entity for Ghost {
-    fn get_name(&self) -> &str {
-        &self.name
+    fn tag(&self) -> Tag {
+        Tag::Ghost
     }
 
     fn get_pos(&self) -> (i32, i32) {
         (self.x, self.y)
     }
 
+    fn get_dir(&self) -> (i8, i8) {
+        self.dir
+    }
+
+    fn get_collider(&self) -> Option<Collider> {
+        if self.house == House::Waiting || self.mode == GhostMode::Eyes {
+            return None;
+        }
+
+        Some(GHOST_COLLIDER)
+    }
+
     fn get_graphics(&self) -> &Graphics {
         &self.graphics
     }
 
-    fn get_color(&mut self) -> &Color {
+    fn get_color(&self) -> &Color {
         &self.color
     }
 
-    fn start(&mut self, _: Rc<RefCell<EntityEnviroment>>) {
-        
+    fn component(&self, id: TypeId) -> Option<&dyn Any> {
+        (id == TypeId::of::<GhostStatus>()).then_some(&self.status as &dyn Any)
     }
 
-    fn update(&mut self, _env: Rc<RefCell<EntityEnviroment>>) {
-        let (target_x, target_y) = if self.forward {
-            (self.goto_x, self.goto_y)
-        } else {
-            (self.start_x, self.start_y)
-        };
-
-        let dx = target_x - self.x;
-        let dy = target_y - self.y;
+    fn start(&mut self, _: &World, _: &mut Commands) {
 
-        let dist = ((dx * dx + dy * dy) as f64).sqrt();
+    }
 
-        if dist < GHOST_SPEED as f64 {
-            self.x = target_x;
-            self.y = target_y;
-            self.forward = !self.forward;
-            return;
-        }
+    fn update(&mut self, world: &World, _: &mut Commands) {
+        self.update_mode();
+        self.step(world);
+        self.update_graphics();
+    }
 
-        let step_x = (dx as f64 / dist * GHOST_SPEED as f64).round() as i32;
-        let step_y = (dy as f64 / dist * GHOST_SPEED as f64).round() as i32;
+    fn on_action(&mut self, _: Action) {
 
-        self.x += step_x;
-        self.y += step_y;
     }
 
-    fn on_key_down(&mut self, _: Keycode) {
-        
+    fn on_message(&mut self, message: &Message) {
+        match message {
+            Message::Frighten(ticks) => self.frighten(*ticks),
+            Message::Eaten => self.become_eyes(),
+            Message::Release => self.release(),
+            Message::Reset => self.reset()
+        }
     }
+
 }
@@ -1,5 +1,8 @@
 use std::path::PathBuf;
 
+use sdl2::rect::Rect;
+
+pub const SPRITE_SIZE: u32 = 32;
 
 #[derive(Clone)]
 pub struct Line {
@@ -10,8 +13,8 @@ pub struct Line {
 impl Line {
     pub fn create(start: (i32, i32), end: (i32, i32)) -> Line {
         Line {
-            start: start,
-            end: end
+            start,
+            end
         }
     }
 
@@ -21,10 +24,91 @@ impl Line {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LoopMode {
+    Once,
+    Loop,
+    PingPong
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    pub sheet: PathBuf,
+    pub frames: Vec<Rect>,
+    pub frame_ticks: u32,
+    pub mode: LoopMode,
+    ticks: u32
+}
+
+impl Animation {
+    pub fn create(sheet: &str, frames: Vec<Rect>, frame_ticks: u32, mode: LoopMode) -> Animation {
+        Animation {
+            sheet: PathBuf::from(sheet),
+            frames,
+            frame_ticks: frame_ticks.max(1),
+            mode,
+            ticks: 0
+        }
+    }
+
+    pub fn set_frames(&mut self, sheet: &str, frames: Vec<Rect>) {
+        self.sheet = PathBuf::from(sheet);
+        self.frames = frames;
+    }
+
+    pub fn step(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+    }
+
+    pub fn reset(&mut self) {
+        self.ticks = 0;
+    }
+
+    pub fn frame(&self) -> Option<Rect> {
+        let count = self.frames.len() as u32;
+        if count == 0 {
+            return None;
+        }
+
+        let index = self.ticks / self.frame_ticks;
+        let index = match self.mode {
+            LoopMode::Once => index.min(count - 1),
+            LoopMode::Loop => index % count,
+            LoopMode::PingPong if count > 1 => {
+                let period = 2 * count - 2;
+                let index = index % period;
+
+                if index < count { index } else { period - index }
+            }
+            LoopMode::PingPong => 0
+        };
+
+        Some(self.frames[index as usize])
+    }
+}
+
+pub fn frames(row: i32, count: i32) -> Vec<Rect> {
+    let size = SPRITE_SIZE as i32;
+
+    (0..count)
+        .map(|column| Rect::new(column * size, row * size, SPRITE_SIZE, SPRITE_SIZE))
+        .collect()
+}
+
+pub fn direction_row(dir: (i8, i8)) -> i32 {
+    match dir {
+        (-1, 0) => 1,
+        (0, -1) => 2,
+        (0, 1) => 3,
+        _ => 0
+    }
+}
+
 #[derive(Clone)]
 pub enum Graphics {
     Line(Vec<Line>),
     Image {
         path: PathBuf
-    }
+    },
+    Sprite(Animation)
 }
\ No newline at end of file
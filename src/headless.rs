@@ -0,0 +1,131 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::{Game, GameState, WorldState, TICKS_PER_SECOND};
+use crate::input::Action;
+
+#[derive(Clone, Copy)]
+pub struct ScriptedInput {
+    pub tick: u64,
+    pub action: Action
+}
+
+const STUCK_TICKS: u32 = 10 * TICKS_PER_SECOND;
+
+pub struct HeadlessRun {
+    pub state: Option<GameState>,
+    pub world: WorldState
+}
+
+#[derive(Debug)]
+pub struct Stuck {
+    pub tick: u64,
+    pub pos: (i32, i32)
+}
+
+impl ScriptedInput {
+    pub fn load(path: &Path) -> io::Result<Vec<ScriptedInput>> {
+        let data = fs::read_to_string(path)?;
+        let invalid = |line: usize, message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), line, message))
+        };
+
+        let mut inputs = Vec::new();
+
+        for (i, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((tick, key)) = line.split_once(char::is_whitespace) else {
+                return Err(invalid(i + 1, "expected `<tick> <action>`".to_string()));
+            };
+
+            let tick = tick.parse::<u64>()
+                .map_err(|_| invalid(i + 1, format!("bad tick `{}`", tick)))?;
+
+            let action = match key.trim() {
+                "up" => Action::MoveUp,
+                "down" => Action::MoveDown,
+                "left" => Action::MoveLeft,
+                "right" => Action::MoveRight,
+                name => Action::from_name(name).ok_or_else(|| invalid(i + 1, format!("unknown action `{}`", name)))?
+            };
+
+            inputs.push(ScriptedInput { tick, action });
+        }
+
+        inputs.sort_by_key(|input| input.tick);
+
+        Ok(inputs)
+    }
+}
+
+impl Game {
+    pub fn run_headless(&mut self, ticks: u64, inputs: &[ScriptedInput]) -> HeadlessRun {
+        let mut inputs = inputs.iter().peekable();
+
+        self.start();
+
+        for tick in 0..ticks {
+            while let Some(input) = inputs.next_if(|input| input.tick <= tick) {
+                if let Some(state) = self.on_action(input.action) {
+                    return HeadlessRun {
+                        state: Some(state),
+                        world: self.world_state()
+                    };
+                }
+            }
+
+            if let Some(state) = self.tick() {
+                return HeadlessRun {
+                    state: Some(state),
+                    world: self.world_state()
+                };
+            }
+        }
+
+        HeadlessRun {
+            state: None,
+            world: self.world_state()
+        }
+    }
+
+    // Runs until the game ends, failing if pacman stands still for too long while playing.
+    pub fn run_soak(&mut self, ticks: u64) -> Result<HeadlessRun, Stuck> {
+        let mut still: Option<((i32, i32), u64)> = None;
+
+        self.start();
+
+        for _ in 0..ticks {
+            if let Some(state) = self.tick() {
+                return Ok(HeadlessRun {
+                    state: Some(state),
+                    world: self.world_state()
+                });
+            }
+
+            let pacman = self.world_state().pacman.filter(|_| self.state() == GameState::Playing);
+
+            still = match (pacman, still) {
+                (Some(pos), Some((last, since))) if pos == last => {
+                    if self.ticks() - since >= STUCK_TICKS as u64 {
+                        return Err(Stuck { tick: since, pos });
+                    }
+
+                    Some((last, since))
+                }
+
+                (Some(pos), _) => Some((pos, self.ticks())),
+                (None, _) => None
+            };
+        }
+
+        Ok(HeadlessRun {
+            state: None,
+            world: self.world_state()
+        })
+    }
+}
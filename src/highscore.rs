@@ -0,0 +1,218 @@
+use std::cmp::Reverse;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::files;
+
+const TABLE_SIZE: usize = 10;
+const TABLE_FILE: &str = "highscores.txt";
+pub(crate) const APP_DIR: &str = "rust-pacman";
+
+#[derive(Clone)]
+pub struct HighScore {
+    pub initials: String,
+    pub date: String,
+    pub score: u32,
+    pub level: usize,
+    pub pellets: u32
+}
+
+pub struct HighScores {
+    path: PathBuf,
+    entries: Vec<HighScore>
+}
+
+impl HighScores {
+    pub fn default_path() -> PathBuf {
+        data_dir().join(APP_DIR).join(TABLE_FILE)
+    }
+
+    pub fn load(path: &Path) -> HighScores {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| parse(&data))
+            .unwrap_or_default();
+
+        HighScores {
+            path: path.to_path_buf(),
+            entries
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    pub fn best(&self) -> u32 {
+        self.entries.first().map(|entry| entry.score).unwrap_or(0)
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < TABLE_SIZE || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, entry: HighScore) {
+        let index = self.entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+
+        self.entries.insert(index, entry);
+        self.entries.truncate(TABLE_SIZE);
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let mut data = String::new();
+        for entry in self.entries.iter() {
+            data.push_str(&format!("{} {} {} {} {}\n", entry.initials, entry.date, entry.score, entry.level, entry.pellets));
+        }
+
+        files::write_atomic(&self.path, &data)
+    }
+}
+
+fn parse(data: &str) -> Option<Vec<HighScore>> {
+    let mut entries = Vec::new();
+
+    for line in data.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let [initials, date, score, level, pellets] = words.as_slice() else {
+            return None;
+        };
+
+        if initials.len() != 3 || !initials.chars().all(|c| c.is_ascii_uppercase()) {
+            return None;
+        }
+
+        entries.push(HighScore {
+            initials: initials.to_string(),
+            date: date.to_string(),
+            score: score.parse().ok()?,
+            level: level.parse().ok()?,
+            pellets: pellets.parse().ok()?
+        });
+    }
+
+    entries.sort_by_key(|entry| Reverse(entry.score));
+    entries.truncate(TABLE_SIZE);
+
+    Some(entries)
+}
+
+pub(crate) fn data_dir() -> PathBuf {
+    let var = |name: &str| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+
+    if cfg!(target_os = "windows") {
+        if let Some(dir) = var("APPDATA") {
+            return dir;
+        }
+    } else if cfg!(target_os = "macos") {
+        if let Some(home) = var("HOME") {
+            return home.join("Library").join("Application Support");
+        }
+    } else {
+        if let Some(dir) = var("XDG_DATA_HOME") {
+            return dir;
+        }
+
+        if let Some(home) = var("HOME") {
+            return home.join(".local").join("share");
+        }
+    }
+
+    PathBuf::from(".")
+}
+
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    fn entry(initials: &str, score: u32) -> HighScore {
+        HighScore {
+            initials: initials.to_string(),
+            date: "2024-01-01".to_string(),
+            score,
+            level: 1,
+            pellets: 0
+        }
+    }
+
+    fn table(scores: &[u32]) -> HighScores {
+        let mut table = HighScores {
+            path: PathBuf::new(),
+            entries: Vec::new()
+        };
+
+        for score in scores {
+            table.insert(entry("AAA", *score));
+        }
+
+        table
+    }
+
+    fn scores(table: &HighScores) -> Vec<u32> {
+        table.entries().iter().map(|entry| entry.score).collect()
+    }
+
+    #[test]
+    fn corrupt_file_loads_as_empty() {
+        let path = env::temp_dir().join(format!("rust-pacman-{}-highscores.txt", process::id()));
+        fs::write(&path, "ABC 2024-01-01 500 1 20\nnot a score line\n").expect("test file writes");
+
+        let table = HighScores::load(&path);
+        fs::remove_file(&path).ok();
+
+        assert!(table.entries().is_empty());
+    }
+
+    #[test]
+    fn insert_keeps_highest_first() {
+        let table = table(&[300, 900, 100, 500]);
+
+        assert_eq!(scores(&table), [900, 500, 300, 100]);
+    }
+
+    #[test]
+    fn insert_keeps_only_the_best() {
+        let table = table(&[10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 5]);
+
+        assert_eq!(scores(&table), [110, 100, 90, 80, 70, 60, 50, 40, 30, 20]);
+        assert!(!table.qualifies(20));
+        assert!(table.qualifies(21));
+    }
+
+    #[test]
+    fn insert_puts_ties_after_earlier_scores() {
+        let mut table = table(&[500, 300]);
+        table.insert(entry("NEW", 300));
+
+        let initials: Vec<&str> = table.entries().iter().map(|entry| entry.initials.as_str()).collect();
+
+        assert_eq!(scores(&table), [500, 300, 300]);
+        assert_eq!(initials, ["AAA", "AAA", "NEW"]);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::entity::EntityId;
+use crate::events::GameEvent;
+use crate::game::Game;
+
+// The arcade switches to one shared counter after a death, releasing ghosts at 7, 17 and 32 pellets.
+const RESTART_PELLETS: [usize; 3] = [7, 10, 15];
+
+pub fn subscribe(game: &mut Game) {
+    game.subscribe(|game, event| game.house().on_event(event));
+}
+
+pub struct GhostHouse {
+    ghosts: Vec<EntityId>,
+    pellets: Vec<usize>,
+    next: usize,
+    counter: usize,
+    idle_ticks: u32,
+    idle: u32
+}
+
+impl GhostHouse {
+    pub fn new(ghosts: Vec<EntityId>, pellets: &[usize], idle_ticks: u32) -> GhostHouse {
+        GhostHouse {
+            ghosts,
+            pellets: pellets.to_vec(),
+            next: 0,
+            counter: 0,
+            idle_ticks,
+            idle: 0
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PelletEaten { .. } | GameEvent::PowerPelletEaten { .. } => self.on_pellet(),
+            GameEvent::PacmanDied => self.restart(),
+            _ => {}
+        }
+    }
+
+    fn restart(&mut self) {
+        self.pellets = RESTART_PELLETS.to_vec();
+        self.next = 0;
+        self.counter = 0;
+        self.idle = 0;
+    }
+
+    fn on_pellet(&mut self) {
+        self.counter += 1;
+        self.idle = 0;
+    }
+
+    pub fn tick(&mut self) -> Option<EntityId> {
+        let id = *self.ghosts.get(self.next)?;
+        let limit = self.pellets.get(self.next).copied().unwrap_or(0);
+
+        self.idle += 1;
+        if self.counter < limit && self.idle < self.idle_ticks {
+            return None;
+        }
+
+        self.next += 1;
+        self.counter = 0;
+        self.idle = 0;
+
+        Some(id)
+    }
+}
+
+impl Default for GhostHouse {
+    fn default() -> GhostHouse {
+        GhostHouse::new(Vec::new(), &[], 0)
+    }
+}
@@ -0,0 +1,67 @@
+use std::io;
+use std::path::Path;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::assets::Textures;
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::fruit::FruitKind;
+
+const TEXT_SCALE: i32 = 3;
+const MARGIN: i32 = 64;
+const LIFE_RADIUS: i16 = 12;
+const LIFE_SPACING: i32 = 32;
+const FRUIT_SIZE: u32 = 32;
+const FRUIT_SPACING: i32 = 36;
+
+pub struct Hud {
+    pub score: u32,
+    pub high_score: u32,
+    pub lives: u32,
+    pub level: usize,
+    pub fruits: Vec<FruitKind>
+}
+
+impl Hud {
+    pub fn draw(&self, canvas: &mut Canvas<Window>, textures: &mut Textures, width: u32, height: u32) -> io::Result<()> {
+        let width = width as i32;
+        let height = height as i32;
+        let line = GLYPH_HEIGHT * TEXT_SCALE;
+
+        font::draw_text(canvas, "1UP", MARGIN, 16, TEXT_SCALE, Color::WHITE)?;
+        font::draw_text(canvas, &self.score.to_string(), MARGIN, 24 + line, TEXT_SCALE, Color::WHITE)?;
+
+        let title = "HIGH SCORE";
+        let high_score = self.high_score.to_string();
+        let center = width / 2;
+        font::draw_text(canvas, title, center - font::text_width(title, TEXT_SCALE) / 2, 16, TEXT_SCALE, Color::WHITE)?;
+        font::draw_text(canvas, &high_score, center - font::text_width(&high_score, TEXT_SCALE) / 2, 24 + line, TEXT_SCALE, Color::WHITE)?;
+
+        let bottom = height - 44;
+        for i in 0..self.lives.saturating_sub(1) {
+            let x = MARGIN + LIFE_RADIUS as i32 + i as i32 * LIFE_SPACING;
+            canvas.filled_pie(x as i16, (bottom + line / 2) as i16, LIFE_RADIUS, 30, 330, Color::YELLOW)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+
+        let level = format!("LEVEL {}", self.level);
+        let level_x = width - MARGIN - font::text_width(&level, TEXT_SCALE);
+        font::draw_text(canvas, &level, level_x, bottom, TEXT_SCALE, Color::WHITE)?;
+
+        // The newest fruit sits next to the level number, older ones further left.
+        for (i, fruit) in self.fruits.iter().rev().enumerate() {
+            let x = level_x - 16 - (i as i32 + 1) * FRUIT_SPACING;
+            let y = bottom + line / 2 - FRUIT_SIZE as i32 / 2;
+            let texture = textures.get(Path::new(fruit.image()))?;
+
+            canvas.copy(texture, None, Some(Rect::new(x, y, FRUIT_SIZE, FRUIT_SIZE)))
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Can't draw a fruit"))?;
+        }
+
+        Ok(())
+    }
+}
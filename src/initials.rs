@@ -0,0 +1,105 @@
+use std::io;
+
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::font;
+use crate::input::Action;
+use crate::options::key_name;
+use crate::settings::Settings;
+
+const INITIALS_LENGTH: usize = 3;
+const TEXT_SCALE: i32 = 4;
+const HINT_SCALE: i32 = 2;
+const LETTER_GAP: i32 = 16;
+
+pub struct InitialsEntry {
+    score: u32,
+    letters: [u8; INITIALS_LENGTH],
+    cursor: usize,
+    done: bool,
+    cancelled: bool
+}
+
+impl InitialsEntry {
+    pub fn new(score: u32) -> InitialsEntry {
+        InitialsEntry {
+            score,
+            letters: [b'A'; INITIALS_LENGTH],
+            cursor: 0,
+            done: false,
+            cancelled: false
+        }
+    }
+
+    pub fn letters(&self) -> String {
+        self.letters.iter().map(|letter| *letter as char).collect()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done || self.cancelled
+    }
+
+    fn cycle(&mut self, delta: i32) {
+        let letter = &mut self.letters[self.cursor];
+        *letter = b'A' + (*letter as i32 - b'A' as i32 + delta).rem_euclid(26) as u8;
+    }
+
+    // Returns whether the action belongs to the entry, so the rest can still mute or change the volume.
+    pub fn on_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::MoveUp => self.cycle(1),
+            Action::MoveDown => self.cycle(-1),
+            Action::MoveLeft => self.cursor = self.cursor.saturating_sub(1),
+            Action::MoveRight => self.cursor = (self.cursor + 1).min(INITIALS_LENGTH - 1),
+            Action::Confirm if self.cursor + 1 < INITIALS_LENGTH => self.cursor += 1,
+            Action::Confirm => self.done = true,
+
+            // A controller has no quit button, so its Back button skips the entry too.
+            Action::Quit | Action::Options => self.cancelled = true,
+
+            _ => return false
+        }
+
+        true
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, settings: &Settings, width: u32, height: u32) -> io::Result<()> {
+        let center = width as i32 / 2;
+        let score = self.score.to_string();
+
+        let lines = [
+            ("NEW HIGH SCORE!", Color::YELLOW),
+            (score.as_str(), Color::WHITE),
+            ("ENTER YOUR INITIALS", Color::WHITE),
+        ];
+
+        for (i, (text, color)) in lines.iter().enumerate() {
+            let x = center - font::text_width(text, TEXT_SCALE) / 2;
+            font::draw_text(canvas, text, x, 120 + i as i32 * 90, TEXT_SCALE, *color)?;
+        }
+
+        let letter_width = font::text_width("W", TEXT_SCALE) + LETTER_GAP;
+        let left = center - letter_width * INITIALS_LENGTH as i32 / 2;
+
+        for (i, letter) in self.letters().chars().enumerate() {
+            let color = if i == self.cursor { Color::YELLOW } else { Color::GREY };
+            font::draw_text(canvas, &letter.to_string(), left + i as i32 * letter_width, 390, TEXT_SCALE, color)?;
+        }
+
+        let hint = format!(
+            "{}/{} TO PICK - {} TO ACCEPT - {} TO SKIP",
+            key_name(settings, Action::MoveUp),
+            key_name(settings, Action::MoveDown),
+            key_name(settings, Action::Confirm),
+            key_name(settings, Action::Quit)
+        );
+
+        font::draw_text(canvas, &hint, center - font::text_width(&hint, HINT_SCALE) / 2, height as i32 - 40, HINT_SCALE, Color::GREY)
+    }
+}
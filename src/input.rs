@@ -0,0 +1,135 @@
+use sdl2::keyboard::Keycode;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Confirm,
+    Pause,
+    Options,
+    Quit,
+    Mute,
+    VolumeDown,
+    VolumeUp,
+    FrameTime
+}
+
+pub const ACTIONS: [Action; 12] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Confirm,
+    Action::Pause,
+    Action::Options,
+    Action::Quit,
+    Action::Mute,
+    Action::VolumeDown,
+    Action::VolumeUp,
+    Action::FrameTime,
+];
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Confirm => "confirm",
+            Action::Pause => "pause",
+            Action::Options => "options",
+            Action::Quit => "quit",
+            Action::Mute => "mute",
+            Action::VolumeDown => "volume_down",
+            Action::VolumeUp => "volume_up",
+            Action::FrameTime => "frame_time"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().copied().find(|action| action.name() == name)
+    }
+
+    pub fn label(&self) -> String {
+        self.name().replace('_', " ").to_uppercase()
+    }
+
+    pub fn direction(&self) -> Option<(i8, i8)> {
+        match self {
+            Action::MoveUp => Some((0, -1)),
+            Action::MoveDown => Some((0, 1)),
+            Action::MoveLeft => Some((-1, 0)),
+            Action::MoveRight => Some((1, 0)),
+            _ => None
+        }
+    }
+
+    pub fn from_direction(dir: (i8, i8)) -> Option<Action> {
+        match dir {
+            (0, -1) => Some(Action::MoveUp),
+            (0, 1) => Some(Action::MoveDown),
+            (-1, 0) => Some(Action::MoveLeft),
+            (1, 0) => Some(Action::MoveRight),
+            _ => None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(Action, Vec<Keycode>)>
+}
+
+impl Default for Bindings {
+    fn default() -> Bindings {
+        Bindings {
+            keys: vec![
+                (Action::MoveUp, vec![Keycode::Up, Keycode::W]),
+                (Action::MoveDown, vec![Keycode::Down, Keycode::S]),
+                (Action::MoveLeft, vec![Keycode::Left, Keycode::A]),
+                (Action::MoveRight, vec![Keycode::Right, Keycode::D]),
+                (Action::Confirm, vec![Keycode::Return, Keycode::KpEnter]),
+                (Action::Pause, vec![Keycode::P]),
+                (Action::Options, vec![Keycode::O]),
+                (Action::Quit, vec![Keycode::Escape]),
+                (Action::Mute, vec![Keycode::M]),
+                (Action::VolumeDown, vec![Keycode::Minus, Keycode::KpMinus]),
+                (Action::VolumeUp, vec![Keycode::Equals, Keycode::KpPlus]),
+                (Action::FrameTime, vec![Keycode::F3]),
+            ]
+        }
+    }
+}
+
+impl Bindings {
+    pub fn action(&self, keycode: Keycode) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&keycode))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> &[Keycode] {
+        self.keys
+            .iter()
+            .find(|(other, _)| *other == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn bind(&mut self, action: Action, keys: Vec<Keycode>) {
+        for (other, other_keys) in self.keys.iter_mut() {
+            if *other != action {
+                other_keys.retain(|key| !keys.contains(key));
+            }
+        }
+
+        match self.keys.iter_mut().find(|(other, _)| *other == action) {
+            Some((_, old)) => *old = keys,
+            None => self.keys.push((action, keys))
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::block::{Block, BlockStyle};
+use crate::campaign::Difficulty;
+use crate::game::Game;
+use crate::ghost::{Ghost, House, Personality};
+use crate::house::GhostHouse;
+use crate::maze::Maze;
+use crate::navigation::{self, DIRECTIONS};
+use crate::pacman::Pacman;
+
+pub const BLOCK_SIZE: i32 = 32;
+pub const START_POS: (i32, i32) = (64, 96);
+
+#[derive(Clone)]
+pub enum Tile {
+    Empty,
+    Wall(BlockStyle),
+    Pellet,
+    PowerPellet,
+    Tunnel
+}
+
+#[derive(Clone)]
+pub struct GhostSpawn {
+    pub personality: Personality,
+    pub spawn: (i32, i32),
+    pub scatter: (i32, i32)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Door {
+    pub tile: (i32, i32),
+    pub exit: (i32, i32),
+    pub inside: (i32, i32)
+}
+
+#[derive(Clone)]
+pub struct Level {
+    pub name: String,
+    pub tiles: Vec<Vec<Tile>>,
+    pub pacman: (i32, i32),
+    pub ghosts: Vec<GhostSpawn>,
+    pub fruit: Option<(i32, i32)>
+}
+
+#[derive(Debug)]
+pub struct LevelError {
+    pub file: PathBuf,
+    // Line and column, or `None` when the problem is with the whole file, like not being able to read it.
+    pub position: Option<(usize, usize)>,
+    pub message: String
+}
+
+impl LevelError {
+    pub(crate) fn new(file: &Path, line: usize, column: usize, message: &str) -> LevelError {
+        LevelError {
+            file: file.to_path_buf(),
+            position: Some((line, column)),
+            message: message.to_string()
+        }
+    }
+
+    pub(crate) fn whole_file(file: &Path, message: &str) -> LevelError {
+        LevelError {
+            file: file.to_path_buf(),
+            position: None,
+            message: message.to_string()
+        }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}:{}: {}", self.file.display(), line, column, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message)
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl Tile {
+    pub fn convert(c: char) -> Option<Tile> {
+        match c {
+            ' ' => Some(Tile::Empty),
+            '.' => Some(Tile::Pellet),
+            'o' => Some(Tile::PowerPellet),
+            't' => Some(Tile::Tunnel),
+            _ => BlockStyle::convert(c).map(Tile::Wall)
+        }
+    }
+}
+
+impl Level {
+    pub(crate) fn tile(&self, (x, y): (i32, i32)) -> Option<&Tile> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        self.tiles.get(y as usize)?.get(x as usize)
+    }
+
+    pub(crate) fn is_passable(&self, tile: (i32, i32)) -> bool {
+        matches!(self.tile(tile), Some(Tile::Empty | Tile::Pellet | Tile::PowerPellet | Tile::Tunnel))
+    }
+
+    fn reachable(&self, start: (i32, i32)) -> HashSet<(i32, i32)> {
+        let mut seen = HashSet::from([start]);
+        let mut open = vec![start];
+
+        while let Some(tile) = open.pop() {
+            for dir in DIRECTIONS {
+                let next = navigation::neighbour(tile, dir);
+
+                if self.is_passable(next) && seen.insert(next) {
+                    open.push(next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    pub fn door(&self) -> Option<Door> {
+        let tile = self.tiles.iter().enumerate().find_map(|(y, row)| {
+            row.iter()
+                .position(|tile| matches!(tile, Tile::Wall(BlockStyle::Door)))
+                .map(|x| (x as i32, y as i32))
+        })?;
+
+        // The side pacman can reach is the way out, the other side is the ghost house.
+        let outside = self.reachable(self.pacman);
+        let sides: Vec<(i32, i32)> = DIRECTIONS
+            .iter()
+            .map(|dir| navigation::neighbour(tile, *dir))
+            .filter(|side| self.is_passable(*side))
+            .collect();
+
+        let exit = sides.iter().copied().find(|side| outside.contains(side))?;
+        let inside = sides.iter().copied().find(|side| !outside.contains(side))?;
+
+        Some(Door { tile, exit, inside })
+    }
+
+    pub fn load(path: &Path) -> Result<Level, LevelError> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| LevelError::whole_file(path, &e.to_string()))?;
+
+        Level::parse(path, &source)
+    }
+
+    pub fn parse(path: &Path, source: &str) -> Result<Level, LevelError> {
+        let mut name = String::new();
+        let mut pacman = None;
+        let mut ghosts = Vec::new();
+        let mut fruit = None;
+        let mut tiles = Vec::new();
+        let mut in_map = false;
+        let mut map_line = 0;
+        let mut last_line = 0;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            last_line = line_number;
+
+            if in_map {
+                let mut row = Vec::new();
+
+                for (x, c) in line.chars().enumerate() {
+                    match Tile::convert(c) {
+                        Some(tile) => row.push(tile),
+                        None => {
+                            let message = format!("unknown tile character '{}'", c);
+                            return Err(LevelError::new(path, line_number, x + 1, &message));
+                        }
+                    }
+                }
+
+                tiles.push(row);
+                continue;
+            }
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if trimmed == "map" {
+                in_map = true;
+                map_line = line_number;
+                continue;
+            }
+
+            let indent = line.len() - line.trim_start().len();
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(LevelError::new(path, line_number, indent + 1, "expected `key = value` or `map`"));
+            };
+
+            let fields = Fields::new(path, line_number, key.len() + 1, value);
+
+            match key.trim() {
+                "name" => {
+                    name = value.trim().to_string();
+                }
+
+                "pacman" => {
+                    let values = fields.numbers(0, 2)?;
+                    pacman = Some((values[0], values[1]));
+                }
+
+                "ghost" => {
+                    let personality = fields.personality(0)?;
+                    let values = fields.numbers(1, 4)?;
+                    ghosts.push(GhostSpawn {
+                        personality,
+                        spawn: (values[0], values[1]),
+                        scatter: (values[2], values[3])
+                    });
+                }
+
+                "fruit" => {
+                    let values = fields.numbers(0, 2)?;
+                    fruit = Some((values[0], values[1]));
+                }
+
+                key => {
+                    let message = format!("unknown key `{}`", key);
+                    return Err(LevelError::new(path, line_number, indent + 1, &message));
+                }
+            }
+        }
+
+        if !in_map {
+            return Err(LevelError::new(path, last_line + 1, 1, "missing `map` section"));
+        }
+
+        let Some(pacman) = pacman else {
+            return Err(LevelError::new(path, map_line, 1, "missing `pacman` spawn before the map"));
+        };
+
+        Ok(Level {
+            name,
+            tiles,
+            pacman,
+            ghosts,
+            fruit
+        })
+    }
+}
+
+struct Fields<'a> {
+    path: &'a Path,
+    line: usize,
+    words: Vec<(usize, &'a str)>
+}
+
+impl<'a> Fields<'a> {
+    fn new(path: &'a Path, line: usize, offset: usize, value: &'a str) -> Fields<'a> {
+        let mut words = Vec::new();
+        let mut end = 0;
+
+        for word in value.split_whitespace() {
+            let start = end + value[end..].find(word).unwrap_or(0);
+            end = start + word.len();
+            words.push((offset + start + 1, word));
+        }
+
+        Fields { path, line, words }
+    }
+
+    fn personality(&self, index: usize) -> Result<Personality, LevelError> {
+        let Some((column, word)) = self.words.get(index) else {
+            return Err(self.missing(index));
+        };
+
+        Personality::convert(word).ok_or_else(|| {
+            let message = format!("unknown ghost `{}`, expected blinky, pinky, inky or clyde", word);
+            LevelError::new(self.path, self.line, *column, &message)
+        })
+    }
+
+    fn numbers(&self, first: usize, count: usize) -> Result<Vec<i32>, LevelError> {
+        let expected = first + count;
+        if self.words.len() > expected {
+            let (column, _) = self.words[expected];
+            let message = format!("expected {} values", expected);
+            return Err(LevelError::new(self.path, self.line, column, &message));
+        }
+
+        let mut values = Vec::new();
+        for index in first..expected {
+            let Some((column, word)) = self.words.get(index) else {
+                return Err(self.missing(index));
+            };
+
+            let value = word.parse::<i32>().map_err(|_| {
+                let message = format!("expected a tile coordinate, found `{}`", word);
+                LevelError::new(self.path, self.line, *column, &message)
+            })?;
+
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    fn missing(&self, index: usize) -> LevelError {
+        let column = match self.words.last() {
+            Some((column, word)) => column + word.len(),
+            None => 1
+        };
+
+        let message = format!("missing value {}", index + 1);
+        LevelError::new(self.path, self.line, column, &message)
+    }
+}
+
+pub fn tile_pos((x, y): (i32, i32)) -> (i32, i32) {
+    (START_POS.0 + x * BLOCK_SIZE, START_POS.1 + y * BLOCK_SIZE)
+}
+
+pub fn generate_map(game: &mut Game, level: &Level, difficulty: &Difficulty) {
+    let mut scores: Vec<(i32, i32)> = Vec::new();
+    let mut power_pellets: Vec<(i32, i32)> = Vec::new();
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let (x, y) = tile_pos((x as i32, y as i32));
+
+            match tile {
+                Tile::Wall(style) => {
+                    let block = Block::new(x, y, style.clone());
+                    game.add_entity(Box::new(block));
+                }
+
+                Tile::Pellet => {
+                    scores.push((x, y));
+                }
+
+                Tile::PowerPellet => {
+                    if power_pellets.len() < difficulty.power_pellets {
+                        power_pellets.push((x, y));
+                    } else {
+                        scores.push((x, y));
+                    }
+                }
+
+                Tile::Empty | Tile::Tunnel => {}
+            }
+        }
+    }
+    
+    let maze = Rc::new(Maze::from_level(level));
+    game.set_maze(maze.clone());
+
+    let (x, y) = tile_pos(level.pacman);
+    let mut pacman = Pacman::new(x, y);
+    pacman.set_maze(maze.clone());
+    game.add_entity(Box::new(pacman));
+
+    let door = level.door();
+    let mut ghosts: Vec<Ghost> = level.ghosts
+        .iter()
+        .map(|spawn| {
+            let (x, y) = tile_pos(spawn.spawn);
+
+            let mut ghost = Ghost::create(spawn.personality, x, y, spawn.scatter);
+            ghost.set_speed(difficulty.ghost_speed);
+            ghost.set_maze(maze.clone());
+            ghost.set_rng(game.rng().fork());
+
+            if let Some(door) = &door {
+                ghost.set_door(door);
+            }
+
+            ghost
+        })
+        .collect();
+
+    ghosts.sort_by_key(|ghost| ghost.personality() != Personality::Blinky);
+
+    let mut blinky = None;
+    let mut waiting = Vec::new();
+    for mut ghost in ghosts {
+        if let Some(partner) = blinky {
+            ghost.set_partner(partner);
+        }
+
+        let in_house = ghost.house() == House::Waiting;
+        let id = game.add_entity(Box::new(ghost));
+
+        if blinky.is_none() {
+            blinky = Some(id);
+        }
+
+        if in_house {
+            waiting.push(id);
+        }
+    }
+    
+    game.set_scores(scores);
+    game.set_power_pellets(power_pellets);
+    game.set_frightened_ticks(difficulty.frightened_ticks);
+    game.set_house(GhostHouse::new(waiting, &difficulty.house_pellets, difficulty.house_idle_ticks));
+    game.set_fruit(level.fruit.map(tile_pos), difficulty.fruit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Level, LevelError> {
+        Level::parse(Path::new("test.lvl"), source)
+    }
+
+    fn error(result: Result<Level, LevelError>) -> LevelError {
+        match result {
+            Ok(_) => panic!("the level should not load"),
+            Err(e) => e
+        }
+    }
+
+    #[test]
+    fn unknown_tile_is_reported_at_its_line_and_column() {
+        let error = error(parse("pacman = 1 1\nmap\n8779\n6.x6\n2774"));
+
+        assert_eq!(error.position, Some((4, 3)));
+        assert_eq!(error.to_string(), "test.lvl:4:3: unknown tile character 'x'");
+    }
+
+    #[test]
+    fn ragged_rows_are_kept_as_written() {
+        let Ok(level) = parse("pacman = 1 1\nmap\n8779\n6.\n2774") else {
+            panic!("ragged rows should still load");
+        };
+        let widths: Vec<usize> = level.tiles.iter().map(|row| row.len()).collect();
+
+        assert_eq!(widths, [4, 2, 4]);
+    }
+
+    #[test]
+    fn missing_pacman_is_reported_at_the_map_line() {
+        let error = error(parse("name = Test\n\nmap\n8779\n2774"));
+
+        assert_eq!(error.position, Some((3, 1)));
+        assert_eq!(error.message, "missing `pacman` spawn before the map");
+    }
+
+    #[test]
+    fn bad_value_is_reported_at_its_column() {
+        let error = error(parse("pacman = 1 x\nmap\n"));
+
+        assert_eq!(error.position, Some((1, 12)));
+        assert_eq!(error.message, "expected a tile coordinate, found `x`");
+    }
+
+    #[test]
+    fn unreadable_file_has_no_position() {
+        let path = Path::new("levels/missing.lvl");
+        let error = error(Level::load(path));
+
+        assert_eq!(error.position, None);
+        assert!(error.to_string().starts_with("levels/missing.lvl: "));
+    }
+}
@@ -0,0 +1,34 @@
+pub mod game;
+pub mod assets;
+pub mod audio;
+pub mod entity;
+pub mod events;
+pub mod pacman;
+pub mod block;
+pub mod bot;
+pub mod campaign;
+pub mod graphics;
+pub mod font;
+pub mod files;
+pub mod frametime;
+pub mod fruit;
+pub mod gamepad;
+pub mod ghost;
+pub mod headless;
+pub mod highscore;
+pub mod house;
+pub mod hud;
+pub mod initials;
+pub mod input;
+pub mod level;
+pub mod maze;
+pub mod navigation;
+pub mod options;
+pub mod pathfinding;
+pub mod replay;
+pub mod rng;
+pub mod screen;
+pub mod settings;
+pub mod speed;
+pub mod validate;
+pub mod world;
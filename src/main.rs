@@ -1,106 +1,448 @@
-mod game;
-mod entity;
-mod pacman;
-mod block;
-mod graphics;
-mod ghost;
-
-use std::path::PathBuf;
-
-use game::{Game, GameState};
-use block::{Block, BlockStyle};
-use ghost::Ghost;
-use graphics::Graphics;
-use pacman::Pacman;
-
-const MAP: &str = concat!(
-    "87777777777777777779\n",
-    "6..................6\n",
-    "6.3.3.87777779.3.3.6\n",
-    "6.2.6.6..54..6.6.2.6\n",
-    "6...6.6......6.6...6\n",
-    "2.3.6.6.3..3.6.6.3.2\n",
-    " .6.6.6.6..6.6.6.6. \n",
-    "3.2.2.2.a77b.2.2.2.3\n",
-    "6..................6\n",
-    "6.87779.5774.87779.6\n",
-    "6.2.3.2......2.3.2.6\n",
-    "6...2...1..1...2...6\n",
-    "a777777777777777777b",
-);
-
-
-const BLOCK_SIZE: i32 = 32;
-const START_POS: (i32, i32) = (64, 96);
-
-
-fn generate_map<'a>(game: &mut Game) {
-    let lines = MAP.split('\n');
-    let mut blocks: Vec<Block> = Vec::new();
-    let mut scores: Vec<(i32, i32)> = Vec::new();
-
-    for (y, line) in lines.enumerate() {
-        let y = START_POS.1 + (y as i32) * BLOCK_SIZE;
-        
-        for (x, c) in line.chars().enumerate() {
-            let x = START_POS.0 + (x as i32) * BLOCK_SIZE;
-
-            match c {
-                '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | 'a' | 'b' => {
-                    let style = BlockStyle::convert(c);
-                    let block = Block::new("block", x , y, style);
-                    blocks.push(block.clone());
-                    game.add_entity(Box::new(block));
-                }
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use rust_pacman::assets;
+use rust_pacman::game::{Game, GameState};
+use rust_pacman::campaign::{Campaign, Difficulty, START_LIVES};
+use rust_pacman::headless::{HeadlessRun, ScriptedInput};
+use rust_pacman::highscore::{self, HighScore, HighScores};
+use rust_pacman::level::{generate_map, Level};
+use rust_pacman::replay::Replay;
+use rust_pacman::rng::Rng;
+use rust_pacman::screen::FrameRate;
+use rust_pacman::settings::Settings;
+use rust_pacman::validate;
+
+const DEFAULT_CAMPAIGN: [&str; 3] = [
+    "./levels/classic.lvl",
+    "./levels/crossroads.lvl",
+    "./levels/fortress.lvl",
+];
+
+const SAVE_FILE: &str = "./campaign.sav";
+
+fn load_level(path: &Path) -> Level {
+    match Level::load(path) {
+        Ok(level) => level,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn default_campaign() -> Vec<PathBuf> {
+    DEFAULT_CAMPAIGN.iter().map(PathBuf::from).collect()
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("Usage: rust-pacman [--resume] [--vsync | --uncapped] [--record <file> | --replay <file>] [--seed <number>] [--headless <ticks> [--script <file>] | --soak <ticks>] [level...]");
+    eprintln!("       rust-pacman validate [level...]");
+    process::exit(2);
+}
+
+fn exit_on_error<T>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    })
+}
+
+fn load_settings() -> Settings {
+    let path = Settings::default_path();
+
+    Settings::load(&path).unwrap_or_else(|e| {
+        eprintln!("Can't load settings, using defaults and leaving {} as it is: {}", path.display(), e);
+        Settings::unsaved()
+    })
+}
+
+// With `--seed` every level draws its seed from the one given, so a whole run can be repeated.
+fn campaign_start(campaign: &Campaign, seeds: &mut Option<Rng>) -> Replay {
+    let seed = seeds.as_mut().map_or_else(Rng::seed_from_time, Rng::next_u64);
+
+    Replay::new(campaign.level_path(), campaign.stage(), seed, campaign.score(), campaign.lives())
+}
+
+fn setup_level(game: &mut Game, start: &Replay) -> Level {
+    let level = load_level(&start.level);
+
+    game.clear();
+    game.set_seed(start.seed);
+    game.set_score(start.score);
+    game.set_lives(start.lives);
+    game.set_level(start.stage + 1);
+    generate_map(game, &level, &Difficulty::for_level(start.stage));
+
+    level
+}
+
+// A replay covers a single level, so the levels after the first go to `run-2.replay`, `run-3.replay` and so on.
+fn recording_path(path: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        return path.to_path_buf();
+    }
+
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, index + 1, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, index + 1)
+    };
+
+    path.with_file_name(name)
+}
+
+fn save_recording(game: &mut Game, record: &Option<PathBuf>, saved: &mut usize) {
+    if let (Some(path), Some(recording)) = (record, game.take_recording()) {
+        let path = recording_path(path, *saved);
+        *saved += 1;
+
+        if let Err(e) = recording.save(&path) {
+            eprintln!("Can't save recording: {}", e);
+        }
+    }
+}
+
+fn print_run(run: &HeadlessRun) {
+    let state = match run.state {
+        Some(state) => format!("{:?}", state),
+        None => "Running".to_string()
+    };
+
+    println!(
+        "{} after {} ticks, score {}, {} lives, {} pellets and {} power pellets left",
+        state, run.world.ticks, run.world.score, run.world.lives, run.world.pellets.len(), run.world.power_pellets.len()
+    );
+}
+
+fn run_headless(campaign: &Campaign, ticks: u64, script: Option<PathBuf>, record: &Option<PathBuf>, seeds: &mut Option<Rng>) {
+    let inputs = match script {
+        Some(path) => exit_on_error(ScriptedInput::load(&path)),
+        None => Vec::new()
+    };
+
+    let mut game = Game::create("Pacman", 800, 600);
+    let start = campaign_start(campaign, seeds);
+    setup_level(&mut game, &start);
+
+    if record.is_some() {
+        game.start_recording(start);
+    }
+
+    let run = game.run_headless(ticks, &inputs);
+    save_recording(&mut game, record, &mut 0);
+    print_run(&run);
+}
+
+fn run_validate(mut levels: Vec<PathBuf>) {
+    let mut failed = false;
+
+    if levels.is_empty() {
+        levels = default_campaign();
+    }
+
+    for path in &levels {
+        let problems = validate::validate(path);
+
+        if problems.is_empty() {
+            println!("{}: ok", path.display());
+        }
+
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+
+        failed |= !problems.is_empty();
+    }
 
-                '.' => {
-                    scores.push((x, y));
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn run_soak(levels: &[PathBuf], ticks: u64, seed: Option<u64>) {
+    let seed = seed.unwrap_or_else(Rng::seed_from_time);
+    let mut seeds = Some(Rng::new(seed));
+    let mut campaign = Campaign::new(levels.to_vec(), START_LIVES);
+    let mut game = Game::create("Pacman", 800, 600);
+    let mut remaining = ticks;
+    let (mut games, mut levels_cleared, mut best_score) = (1, 0, 0);
+
+    while remaining > 0 {
+        let start = campaign_start(&campaign, &mut seeds);
+        setup_level(&mut game, &start);
+        game.set_autopilot(true);
+
+        let place = format!("{} (stage {}), rerun with --soak {} --seed {}", start.level.display(), start.stage, ticks, seed);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| game.run_soak(remaining)));
+
+        let run = match result {
+            Ok(Ok(run)) => run,
+
+            Ok(Err(stuck)) => {
+                eprintln!("Pacman got stuck at {:?} from tick {} on {}", stuck.pos, stuck.tick, place);
+                process::exit(1);
+            }
+
+            Err(_) => {
+                eprintln!("Crashed on {}", place);
+                process::exit(1);
+            }
+        };
+
+        remaining = remaining.saturating_sub(run.world.ticks);
+        best_score = best_score.max(run.world.score);
+
+        match run.state {
+            Some(GameState::Win) => {
+                levels_cleared += 1;
+
+                if !campaign.advance(game.score(), game.lives(), game.pellets_eaten()) {
+                    campaign = Campaign::new(levels.to_vec(), START_LIVES);
+                    games += 1;
                 }
+            }
 
-                _ => {}
+            Some(GameState::Lose) => {
+                campaign = Campaign::new(levels.to_vec(), START_LIVES);
+                games += 1;
             }
+
+            _ => break
+        }
+    }
+
+    println!("Soaked {} ticks over {} games: {} levels cleared, best score {}", ticks - remaining, games, levels_cleared, best_score);
+}
+
+fn run_replay(path: &Path, headless: bool, frame_rate: FrameRate) {
+    let replay = exit_on_error(Replay::load(path));
+    let length = replay.length();
+
+    if !headless {
+        exit_on_error(assets::check());
+    }
+
+    let settings = load_settings();
+    let mut game = Game::create("Pacman - Replay", settings.width, settings.height);
+    game.set_frame_rate(frame_rate);
+    game.set_settings(settings);
+    setup_level(&mut game, &replay);
+    game.start_playback(replay);
+
+    if headless {
+        print_run(&game.run_headless(length, &[]));
+    } else {
+        exit_on_error(game.run());
+    }
+
+    match game.divergence() {
+        Some(divergence) => {
+            println!("Replay diverged at tick {}: expected {:016x}, got {:016x}", divergence.tick, divergence.expected, divergence.actual);
+            process::exit(1);
         }
+
+        None => println!("Replay matched")
     }
-    
-    let mut pacman = Pacman::new("pacman", START_POS.0, START_POS.1 + 6 * BLOCK_SIZE);
-    pacman.set_blocks(blocks);
-    game.add_entity(Box::new(pacman));
+}
+
+fn record_high_score(game: &mut Game, high_scores: &mut HighScores, level: usize) -> GameState {
+    if !high_scores.qualifies(game.score()) {
+        return GameState::Title;
+    }
+
+    game.enter_initials();
+    let state = exit_on_error(game.run());
 
-    let ghost = Ghost::create("ghost", START_POS.0 + BLOCK_SIZE * 2, START_POS.1 + BLOCK_SIZE, START_POS.0 + BLOCK_SIZE * 17, START_POS.1 + BLOCK_SIZE, Graphics::Image { path: PathBuf::from("./res/red.jpg") });
-    game.add_entity(Box::new(ghost));
+    let Some(initials) = game.take_initials() else {
+        return state;
+    };
 
-    let ghost = Ghost::create("ghost", START_POS.0 + BLOCK_SIZE * 17, START_POS.1 + BLOCK_SIZE * 8, START_POS.0 + BLOCK_SIZE * 2, START_POS.1 + BLOCK_SIZE * 8, Graphics::Image { path: PathBuf::from("./res/orange.jpg") });
-    game.add_entity(Box::new(ghost));
+    high_scores.insert(HighScore {
+        initials,
+        date: highscore::today(),
+        score: game.score(),
+        level,
+        pellets: game.pellets_eaten()
+    });
+
+    if let Err(e) = high_scores.save() {
+        eprintln!("Can't save high scores: {}", e);
+    }
+
+    for (i, entry) in high_scores.entries().iter().enumerate() {
+        println!("{:2}. {} {:>8} level {:<3} {} pellets {}", i + 1, entry.initials, entry.score, entry.level, entry.pellets, entry.date);
+    }
 
-    let ghost = Ghost::create("ghost", START_POS.0 + BLOCK_SIZE * 7, START_POS.1 + BLOCK_SIZE * 3, START_POS.0 + BLOCK_SIZE * 7, START_POS.1 + BLOCK_SIZE * 11, Graphics::Image { path: PathBuf::from("./res/pink.jpg") });
-    game.add_entity(Box::new(ghost));
-    
-    game.set_scores(scores);
+    state
 }
 
 fn main() {
-    let mut game = Game::create("Pacman", 800, 600);
-    generate_map(&mut game);
-    let mut result = game.run().unwrap();
-    
+    let mut resume = false;
+    let mut frame_rate = FrameRate::Capped;
+    let mut headless: Option<u64> = None;
+    let mut soak: Option<u64> = None;
+    let mut seed: Option<u64> = None;
+    let mut script: Option<PathBuf> = None;
+    let mut record: Option<PathBuf> = None;
+    let mut replay: Option<PathBuf> = None;
+    let mut levels: Vec<PathBuf> = Vec::new();
+
+    let mut args = env::args().skip(1).peekable();
+
+    if args.next_if(|arg| arg == "validate").is_some() {
+        run_validate(args.map(PathBuf::from).collect());
+        return;
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--resume" => resume = true,
+            "--vsync" => frame_rate = FrameRate::VSync,
+            "--uncapped" => frame_rate = FrameRate::Uncapped,
+
+            "--headless" => {
+                let ticks = args.next().and_then(|ticks| ticks.parse().ok());
+                headless = Some(ticks.unwrap_or_else(|| usage_error("--headless needs a tick count")));
+            }
+
+            "--soak" => {
+                let ticks = args.next().and_then(|ticks| ticks.parse().ok());
+                soak = Some(ticks.unwrap_or_else(|| usage_error("--soak needs a tick count")));
+            }
+
+            "--seed" => {
+                let value = args.next().and_then(|seed| seed.parse().ok());
+                seed = Some(value.unwrap_or_else(|| usage_error("--seed needs a number")));
+            }
+
+            "--script" => {
+                let path = args.next().unwrap_or_else(|| usage_error("--script needs a file"));
+                script = Some(PathBuf::from(path));
+            }
+
+            "--record" => {
+                let path = args.next().unwrap_or_else(|| usage_error("--record needs a file"));
+                record = Some(PathBuf::from(path));
+            }
+
+            "--replay" => {
+                let path = args.next().unwrap_or_else(|| usage_error("--replay needs a file"));
+                replay = Some(PathBuf::from(path));
+            }
+
+            _ => levels.push(PathBuf::from(arg))
+        }
+    }
+
+    if let Some(path) = replay {
+        run_replay(&path, headless.is_some(), frame_rate);
+        return;
+    }
+
+    if levels.is_empty() {
+        levels = default_campaign();
+    }
+
+    if let Some(ticks) = soak {
+        run_soak(&levels, ticks, seed);
+        return;
+    }
+
+    let settings = load_settings();
+    let save_path = Path::new(SAVE_FILE);
+    let mut campaign = if resume {
+        match Campaign::load(save_path) {
+            Ok(campaign) => campaign,
+            Err(e) => {
+                eprintln!("Can't resume: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        Campaign::new(levels.clone(), settings.lives)
+    };
+
+    let mut seeds = seed.map(Rng::new);
+
+    if let Some(ticks) = headless {
+        run_headless(&campaign, ticks, script, &record, &mut seeds);
+        return;
+    }
+
+    exit_on_error(assets::check());
+
+    let mut game = Game::create("Pacman", settings.width, settings.height);
+    game.set_frame_rate(frame_rate);
+    game.set_settings(settings);
+
+    let mut high_scores = HighScores::load(&HighScores::default_path());
+    let mut show_title = true;
+    let mut recordings = 0;
+
     'running: loop {
+        let start = campaign_start(&campaign, &mut seeds);
+        let level = setup_level(&mut game, &start);
+        game.set_title(&format!("Pacman - Level {} {}", campaign.level_number(), level.name));
+        game.set_high_score(high_scores.best());
+        game.set_pellets_eaten(campaign.pellets());
+
+        if show_title {
+            game.set_state(GameState::Title);
+            show_title = false;
+        }
+
+        if record.is_some() {
+            game.start_recording(start);
+        }
+
+        let result = exit_on_error(game.run());
+        save_recording(&mut game, &record, &mut recordings);
+
         match result {
             GameState::Win => {
-                println!("You won!");
+                if !campaign.advance(game.score(), game.lives(), game.pellets_eaten()) {
+                    println!("You won! Score: {}", game.score());
+                    Campaign::clear_save(save_path);
 
-                break 'running;
+                    if record_high_score(&mut game, &mut high_scores, campaign.level_number() - 1) == GameState::Close {
+                        break 'running;
+                    }
+
+                    campaign = Campaign::new(levels.clone(), game.settings().lives);
+                    show_title = true;
+                }
             }
 
             GameState::Lose => {
-                game.clear();
-                generate_map(&mut game);
-                result = game.run().unwrap();
+                println!("Game over! Score: {}", game.score());
+                Campaign::clear_save(save_path);
+
+                if record_high_score(&mut game, &mut high_scores, campaign.level_number()) == GameState::Close {
+                    break 'running;
+                }
+
+                campaign = Campaign::new(levels.clone(), game.settings().lives);
+                show_title = true;
             }
 
-            GameState::Close => {
+            // The attract mode demo ended, so set the level up again for a real game.
+            GameState::Title => show_title = true,
+
+            _ => {
+                if game.state() != GameState::Title
+                    && let Err(e) = campaign.save(save_path) {
+                    eprintln!("Can't save progress: {}", e);
+                }
+
                 break 'running;
             }
         }
-    } 
-}
+    }
 
+    game.save_settings();
+}
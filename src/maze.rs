@@ -0,0 +1,101 @@
+use crate::block::BlockStyle;
+use crate::level::{Level, Tile, BLOCK_SIZE, START_POS};
+
+#[derive(Default)]
+pub struct Maze {
+    width: i32,
+    height: i32,
+    walls: Vec<bool>,
+    doors: Vec<bool>,
+    tunnels: Vec<bool>
+}
+
+impl Maze {
+    pub fn from_level(level: &Level) -> Maze {
+        let width = level.tiles.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+        let height = level.tiles.len() as i32;
+        let mut walls = vec![false; (width * height) as usize];
+        let mut doors = vec![false; (width * height) as usize];
+        let mut tunnels = vec![false; (width * height) as usize];
+
+        for (y, row) in level.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                match tile {
+                    Tile::Wall(BlockStyle::Door) => doors[y * width as usize + x] = true,
+                    Tile::Wall(_) => walls[y * width as usize + x] = true,
+                    Tile::Tunnel => tunnels[y * width as usize + x] = true,
+                    _ => {}
+                }
+            }
+        }
+
+        Maze { width, height, walls, doors, tunnels }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some((y * self.width + x) as usize)
+    }
+
+    pub fn wrap_tile(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        if self.width == 0 || self.height == 0 {
+            return (x, y);
+        }
+
+        (x.rem_euclid(self.width), y.rem_euclid(self.height))
+    }
+
+    pub fn is_wall(&self, tile: (i32, i32)) -> bool {
+        match self.index(tile) {
+            Some(index) => self.walls[index],
+            None => !self.is_tunnel(tile)
+        }
+    }
+
+    pub fn is_door(&self, tile: (i32, i32)) -> bool {
+        match self.index(tile) {
+            Some(index) => self.doors[index],
+            None => false
+        }
+    }
+
+    pub fn is_tunnel(&self, tile: (i32, i32)) -> bool {
+        match self.index(self.wrap_tile(tile)) {
+            Some(index) => self.tunnels[index],
+            None => false
+        }
+    }
+
+    pub fn wrap_pos(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let (tile_x, tile_y) = self.tile_at((x, y));
+        let (wrapped_x, wrapped_y) = self.wrap_tile((tile_x, tile_y));
+
+        if !self.is_tunnel((tile_x, tile_y)) {
+            return (x, y);
+        }
+
+        (x + (wrapped_x - tile_x) * BLOCK_SIZE, y + (wrapped_y - tile_y) * BLOCK_SIZE)
+    }
+
+    pub fn tile_at(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let x = (x - START_POS.0) as f64 / BLOCK_SIZE as f64;
+        let y = (y - START_POS.1) as f64 / BLOCK_SIZE as f64;
+
+        (x.round() as i32, y.round() as i32)
+    }
+
+    pub fn offset(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        ((x - START_POS.0).rem_euclid(BLOCK_SIZE), (y - START_POS.1).rem_euclid(BLOCK_SIZE))
+    }
+}
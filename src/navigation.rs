@@ -0,0 +1,114 @@
+use std::rc::Rc;
+
+use crate::level::{self, BLOCK_SIZE};
+use crate::maze::Maze;
+
+pub const DIRECTIONS: [(i8, i8); 4] = [(0, -1), (-1, 0), (0, 1), (1, 0)];
+
+const TURN_WINDOW: i32 = 8;
+
+pub fn neighbour((x, y): (i32, i32), (dir_x, dir_y): (i8, i8)) -> (i32, i32) {
+    (x + dir_x as i32, y + dir_y as i32)
+}
+
+pub fn reverse((dir_x, dir_y): (i8, i8)) -> (i8, i8) {
+    (-dir_x, -dir_y)
+}
+
+#[derive(Clone, Default)]
+pub struct Navigator {
+    maze: Rc<Maze>,
+    doors_open: bool
+}
+
+impl Navigator {
+    pub fn new(maze: Rc<Maze>) -> Navigator {
+        Navigator { maze, doors_open: false }
+    }
+
+    pub fn set_doors_open(&mut self, open: bool) {
+        self.doors_open = open;
+    }
+
+    pub fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    pub fn tile_of(&self, pos: (i32, i32)) -> (i32, i32) {
+        self.maze.tile_at(pos)
+    }
+
+    pub fn center_of(&self, tile: (i32, i32)) -> (i32, i32) {
+        level::tile_pos(tile)
+    }
+
+    pub fn is_centered(&self, pos: (i32, i32)) -> bool {
+        self.maze.offset(pos) == (0, 0)
+    }
+
+    pub fn is_open(&self, tile: (i32, i32), dir: (i8, i8)) -> bool {
+        let next = neighbour(tile, dir);
+
+        dir != (0, 0) && !self.maze.is_wall(next) && (self.doors_open || !self.maze.is_door(next))
+    }
+
+    pub fn exits(&self, tile: (i32, i32)) -> Vec<(i8, i8)> {
+        DIRECTIONS
+            .iter()
+            .copied()
+            .filter(|dir| self.is_open(tile, *dir))
+            .collect()
+    }
+
+    pub fn can_turn(&self, pos: (i32, i32), dir: (i8, i8)) -> bool {
+        let tile = self.tile_of(pos);
+        let (center_x, center_y) = self.center_of(tile);
+        let distance = (pos.0 - center_x).abs() + (pos.1 - center_y).abs();
+
+        distance <= TURN_WINDOW && self.is_open(tile, dir)
+    }
+
+    pub fn snap(&self, pos: (i32, i32)) -> (i32, i32) {
+        self.center_of(self.tile_of(pos))
+    }
+
+    fn to_center(&self, pos: (i32, i32), dir: (i8, i8)) -> i32 {
+        let (offset_x, offset_y) = self.maze.offset(pos);
+        let offset = if dir.0 != 0 { offset_x } else { offset_y };
+
+        match (dir.0 + dir.1 > 0, offset) {
+            (_, 0) => BLOCK_SIZE,
+            (true, offset) => BLOCK_SIZE - offset,
+            (false, offset) => offset
+        }
+    }
+
+    pub fn travel<F>(&self, pos: (i32, i32), dir: (i8, i8), pixels: i32, mut choose: F) -> ((i32, i32), (i8, i8))
+    where
+        F: FnMut((i32, i32), (i8, i8)) -> (i8, i8)
+    {
+        let (mut x, mut y) = pos;
+        let mut dir = dir;
+        let mut remaining = pixels;
+
+        while remaining > 0 {
+            if self.is_centered((x, y)) {
+                (x, y) = self.maze.wrap_pos((x, y));
+
+                let tile = self.tile_of((x, y));
+                dir = choose(tile, dir);
+
+                if !self.is_open(tile, dir) {
+                    break;
+                }
+            }
+
+            let step = remaining.min(self.to_center((x, y), dir));
+            x += dir.0 as i32 * step;
+            y += dir.1 as i32 * step;
+            remaining -= step;
+        }
+
+        ((x, y), dir)
+    }
+}
@@ -0,0 +1,160 @@
+use std::io;
+
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use crate::audio;
+use crate::font;
+use crate::game::GameState;
+use crate::input::{Action, ACTIONS};
+use crate::settings::{Settings, MAX_LIVES, MAX_SCALE};
+
+const TITLE_SCALE: i32 = 3;
+const TEXT_SCALE: i32 = 2;
+const LINE_HEIGHT: i32 = 26;
+const TOP: i32 = 40;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Item {
+    Volume,
+    Fullscreen,
+    Scale,
+    Lives,
+    Bind(Action),
+    Back
+}
+
+pub struct OptionsMenu {
+    items: Vec<Item>,
+    selected: usize,
+    capturing: bool,
+    back_to: GameState
+}
+
+pub fn key_name(settings: &Settings, action: Action) -> String {
+    settings.bindings
+        .keys(action)
+        .first()
+        .map(|key| key.name().to_uppercase())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn step(value: u32, delta: i32, min: u32, max: u32) -> u32 {
+    (value as i32 + delta).clamp(min as i32, max as i32) as u32
+}
+
+impl OptionsMenu {
+    pub fn new(back_to: GameState) -> OptionsMenu {
+        let mut items = vec![Item::Volume, Item::Fullscreen, Item::Scale, Item::Lives];
+        items.extend(ACTIONS.iter().map(|action| Item::Bind(*action)));
+        items.push(Item::Back);
+
+        OptionsMenu {
+            items,
+            selected: 0,
+            capturing: false,
+            back_to
+        }
+    }
+
+    pub fn back_to(&self) -> GameState {
+        self.back_to
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capturing
+    }
+
+    pub fn on_action(&mut self, action: Action, settings: &mut Settings) -> bool {
+        if self.capturing {
+            self.capturing = false;
+            return false;
+        }
+
+        let item = self.items[self.selected];
+
+        match action {
+            Action::MoveUp => self.selected = (self.selected + self.items.len() - 1) % self.items.len(),
+            Action::MoveDown => self.selected = (self.selected + 1) % self.items.len(),
+            Action::MoveLeft => self.adjust(item, -1, settings),
+            Action::MoveRight => self.adjust(item, 1, settings),
+
+            Action::Confirm => match item {
+                Item::Bind(_) => self.capturing = true,
+                Item::Back => return true,
+                item => self.adjust(item, 1, settings)
+            },
+
+            Action::Quit => return true,
+            _ => {}
+        }
+
+        false
+    }
+
+    pub fn capture(&mut self, keycode: Keycode, settings: &mut Settings) {
+        self.capturing = false;
+
+        // Escape cancels, and a comma would break the list in the settings file.
+        if matches!(keycode, Keycode::Escape | Keycode::Comma) {
+            return;
+        }
+
+        if let Item::Bind(action) = self.items[self.selected] {
+            settings.bindings.bind(action, vec![keycode]);
+        }
+    }
+
+    fn adjust(&self, item: Item, delta: i32, settings: &mut Settings) {
+        match item {
+            Item::Volume => settings.volume = step(settings.volume as u32, delta, 0, audio::MAX_VOLUME as u32) as u8,
+            Item::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Item::Scale => settings.scale = step(settings.scale, delta, 1, MAX_SCALE),
+            Item::Lives => settings.lives = step(settings.lives, delta, 1, MAX_LIVES),
+            Item::Bind(_) | Item::Back => {}
+        }
+    }
+
+    fn label(&self, index: usize, settings: &Settings) -> String {
+        let on_off = |flag: bool| if flag { "ON" } else { "OFF" };
+
+        match self.items[index] {
+            Item::Volume => format!("VOLUME: {}", settings.volume),
+            Item::Fullscreen => format!("FULLSCREEN: {}", on_off(settings.fullscreen)),
+            Item::Scale => format!("WINDOW SCALE: {}", settings.scale),
+            Item::Lives => format!("STARTING LIVES: {}", settings.lives),
+
+            Item::Bind(action) if self.capturing && index == self.selected => {
+                format!("{}: PRESS A KEY", action.label())
+            }
+
+            Item::Bind(action) => {
+                let keys: Vec<String> = settings.bindings.keys(action).iter().map(|key| key.name().to_uppercase()).collect();
+                let keys = if keys.is_empty() { "-".to_string() } else { keys.join(" / ") };
+
+                format!("{}: {}", action.label(), keys)
+            }
+
+            Item::Back => "BACK".to_string()
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, settings: &Settings, width: u32, height: u32) -> io::Result<()> {
+        let center = width as i32 / 2;
+        let left = center - 240;
+
+        font::draw_text(canvas, "OPTIONS", center - font::text_width("OPTIONS", TITLE_SCALE) / 2, TOP, TITLE_SCALE, Color::YELLOW)?;
+
+        for index in 0..self.items.len() {
+            let y = TOP + 50 + index as i32 * LINE_HEIGHT;
+            let (prefix, color) = if index == self.selected { ("> ", Color::YELLOW) } else { ("  ", Color::WHITE) };
+
+            font::draw_text(canvas, &format!("{}{}", prefix, self.label(index, settings)), left, y, TEXT_SCALE, color)?;
+        }
+
+        let hint = format!("{} TO CHANGE - {} TO GO BACK", key_name(settings, Action::Confirm), key_name(settings, Action::Quit));
+        font::draw_text(canvas, &hint, center - font::text_width(&hint, TEXT_SCALE) / 2, height as i32 - 40, TEXT_SCALE, Color::GREY)
+    }
+}
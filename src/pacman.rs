@@ -1,78 +1,101 @@
-use std::cell::RefCell;
-use std::path::PathBuf;
 use std::rc::Rc;
 
-use crate::block::Block;
-use crate::entity::{Entity, EntityEnviroment};
-use crate::graphics::Graphics;
+use crate::assets;
+use crate::entity::{Collider, Entity, Message, Tag};
+use crate::graphics::{self, Animation, Graphics, LoopMode};
+use crate::input::Action;
+use crate::maze::Maze;
+use crate::navigation::{self, Navigator};
+use crate::speed::Stride;
+use crate::world::{Commands, World};
 
-
-use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
+use sdl2::rect::Rect;
 
-const PACMAN_SPEED: i32 = 4;
-const PACMAN_STEP: f64 = 0.05;
-
-const PACMAN_RIGHT: &str = "./res/pacman_right.jpg";
-const PACMAN_LEFT: &str = "./res/pacman_left.jpg";
-const PACMAN_UP: &str = "./res/pacman_up.jpg";
-const PACMAN_DOWN: &str = "./res/pacman_down.jpg";
+const PACMAN_SPEED: f64 = 7.5;
+const CHOMP_FRAMES: i32 = 3;
+const CHOMP_TICKS: u32 = 3;
+const PACMAN_COLLIDER: Collider = Collider { half_size: 8 };
 
 pub struct Pacman {
-    name: String,
     x: i32,
     y: i32,
+    spawn: (i32, i32),
     circle: Graphics,
     color: Color,
     dir: (i8, i8),
     wish_dir: (i8, i8),
-    blocks: Vec<Block>
+    nav: Navigator,
+    stride: Stride
 }
 
 impl Pacman {
-    pub fn new(name: &str, x: i32, y: i32) -> Pacman {    
+    pub fn new(x: i32, y: i32) -> Pacman {    
         Pacman {
-            name: name.to_string(),
-            x: x,
-            y: y,
-            circle: Graphics::Image { path: PathBuf::from(PACMAN_RIGHT) },
+            x,
+            y,
+            spawn: (x, y),
+            circle: Graphics::Sprite(Animation::create(assets::PACMAN, chomp_frames((1, 0)), CHOMP_TICKS, LoopMode::PingPong)),
             color: Color::YELLOW,
             dir: (1, 0),
             wish_dir: (0, 0),
-            blocks: Vec::new()
+            nav: Navigator::default(),
+            stride: Stride::default()
         }
     }
     
-    fn step_move(&mut self) {
-        if self.wish_dir == (0, 0) {
-            return;
+    fn face(&mut self, dir: (i8, i8)) {
+        self.dir = dir;
+
+        if let Graphics::Sprite(animation) = &mut self.circle {
+            animation.set_frames(assets::PACMAN, chomp_frames(self.dir));
         }
-        
-        self.dir = self.wish_dir;
-
-        match self.dir {
-            (1, 0) => self.circle = Graphics::Image { path: PathBuf::from(PACMAN_RIGHT) },
-            (-1, 0) => self.circle = Graphics::Image { path: PathBuf::from(PACMAN_LEFT) },
-            (0, 1) => self.circle = Graphics::Image { path: PathBuf::from(PACMAN_DOWN) },
-            (0, -1) => self.circle = Graphics::Image { path: PathBuf::from(PACMAN_UP) },
-            _ => {}
+    }
+
+    fn steer(&mut self) {
+        let wish_dir = self.wish_dir;
+        let pos = (self.x, self.y);
+
+        if wish_dir == (0, 0) {
+            return;
         }
 
-        self.x = (((self.x as f64) / 32.0).round() as i32) * 32;
-        self.y = (((self.y as f64) / 32.0).round() as i32) * 32;
+        if wish_dir == self.dir {
+            self.wish_dir = (0, 0);
+        } else if wish_dir == navigation::reverse(self.dir) {
+            self.face(wish_dir);
+            self.wish_dir = (0, 0);
+        } else if self.nav.can_turn(pos, wish_dir) {
+            (self.x, self.y) = self.nav.snap(pos);
+            self.face(wish_dir);
+            self.wish_dir = (0, 0);
+        }
+    }
 
+    fn reset(&mut self) {
+        (self.x, self.y) = self.spawn;
         self.wish_dir = (0, 0);
+        self.stride.reset();
+        self.face((1, 0));
+
+        if let Graphics::Sprite(animation) = &mut self.circle {
+            animation.reset();
+        }
     }
 
-    pub fn set_blocks(&mut self, blocks: Vec<Block>) {
-        self.blocks = blocks;
+    pub fn set_maze(&mut self, maze: Rc<Maze>) {
+        self.nav = Navigator::new(maze);
     }
 }
 
+fn chomp_frames(dir: (i8, i8)) -> Vec<Rect> {
+    graphics::frames(graphics::direction_row(dir), CHOMP_FRAMES)
+}
+
 
 impl Entity for Pacman {
-    fn get_name(&self) -> &str {
-        &self.name
+    fn tag(&self) -> Tag {
+        Tag::Pacman
     }
 
     fn get_graphics(&self) -> &Graphics {        
@@ -83,94 +106,55 @@ impl Entity for Pacman {
         (self.x, self.y)
     }
 
-    fn get_color(&mut self) -> &Color {
+    fn get_dir(&self) -> (i8, i8) {
+        self.dir
+    }
+
+    fn get_collider(&self) -> Option<Collider> {
+        Some(PACMAN_COLLIDER)
+    }
+
+    fn get_color(&self) -> &Color {
         &self.color
     }
 
-    fn start(&mut self, _: Rc<RefCell<EntityEnviroment>>) { }
+    fn start(&mut self, _: &World, _: &mut Commands) { }
 
-    fn update(&mut self, _: Rc<RefCell<EntityEnviroment>>) {
-        let x = self.x as f64;
-        let y = self.y as f64;
-    
-        let delta_x = x / 32.0 - ((self.x / 32) as f64);
-        let delta_y = y / 32.0 - ((self.y / 32) as f64);
+    fn update(&mut self, _: &World, _: &mut Commands) {
+        let start = (self.x, self.y);
 
-        let mut blocked = false;
-    
-        let mut new_x = self.x + (self.dir.0 as i32) * PACMAN_SPEED;
-        let mut new_y = self.y + (self.dir.1 as i32) * PACMAN_SPEED;
+        self.steer();
 
-        let left = new_x;
-        let right = new_x + 32;
-        let top = self.y;
-        let bottom = self.y + 32;
-    
-        for block in self.blocks.iter() {
-            let (bx, by) = block.get_pos();
-            if right > bx && left < bx + 32 && bottom > by && top < by + 32 {
-                new_x = self.x;
-                blocked = true;
-                break;
-            }
-
-            blocked = false;
-        }
-    
-        self.x = new_x;
-    
-        let left = self.x;
-        let right = self.x + 32;
-        let top = new_y;
-        let bottom = new_y + 32;
-    
-        for block in self.blocks.iter() {
-            let (bx, by) = block.get_pos();
+        let nav = self.nav.clone();
+        let wish_dir = self.wish_dir;
+        let speed = self.stride.next(PACMAN_SPEED);
 
-            if blocked {
-                break;
-            }
+        let (pos, dir) = nav.travel((self.x, self.y), self.dir, speed, |tile, dir| {
+            if nav.is_open(tile, wish_dir) { wish_dir } else { dir }
+        });
 
-            if right > bx && left < bx + 32 && bottom > by && top < by + 32 {
-                new_y = self.y;
-                blocked = true;
-                break;
-            }
+        (self.x, self.y) = pos;
 
-            blocked = false;
+        if dir != self.dir {
+            self.face(dir);
+            self.wish_dir = (0, 0);
         }
 
-        self.y = new_y;
-
-        if blocked {
-            self.step_move();
-        } else {
-            if delta_x < PACMAN_STEP && delta_y < PACMAN_STEP {
-                self.step_move();
-            }
+        if (self.x, self.y) != start
+            && let Graphics::Sprite(animation) = &mut self.circle {
+            animation.step();
         }
     }
-    
-
-    fn on_key_down(&mut self, keycode: Keycode) {
-        match keycode {
-            Keycode::RIGHT | Keycode::D => {
-                self.wish_dir = (1, 0);
-            }
 
-            Keycode::Left | Keycode::A => {
-                self.wish_dir = (-1, 0);
-            }
-
-            Keycode::DOWN | Keycode::S => {
-                self.wish_dir = (0, 1);
-            }
-
-            Keycode::UP | Keycode::W => {
-                self.wish_dir = (0, -1);
-            }
+    fn on_action(&mut self, action: Action) {
+        if let Some(dir) = action.direction() {
+            self.wish_dir = dir;
+        }
+    }
 
-            _ => {}
+    fn on_message(&mut self, message: &Message) {
+        if let Message::Reset = message {
+            self.reset();
         }
     }
 }
@@ -0,0 +1,251 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::maze::Maze;
+use crate::navigation::{self, DIRECTIONS};
+
+pub struct Graph {
+    width: i32,
+    height: i32,
+    open: Vec<bool>,
+    links: Vec<[Option<usize>; 4]>
+}
+
+pub struct DistanceMap {
+    width: i32,
+    height: i32,
+    distances: Vec<Option<u32>>
+}
+
+impl DistanceMap {
+    pub fn get(&self, (x, y): (i32, i32)) -> Option<u32> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.distances[(y * self.width + x) as usize]
+    }
+
+    pub fn reachable(&self) -> impl Iterator<Item = ((i32, i32), u32)> + '_ {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter_map(|(index, distance)| distance.map(|distance| (tile_of(self.width, index), distance)))
+    }
+}
+
+fn tile_of(width: i32, index: usize) -> (i32, i32) {
+    (index as i32 % width, index as i32 / width)
+}
+
+impl Graph {
+    pub fn new(maze: &Maze, doors_open: bool) -> Graph {
+        let width = maze.width();
+        let height = maze.height();
+        let passable = |tile: (i32, i32)| !maze.is_wall(tile) && (doors_open || !maze.is_door(tile));
+
+        let mut open = Vec::with_capacity((width * height) as usize);
+        let mut links = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let tile = (x, y);
+                let mut tile_links = [None; 4];
+
+                if passable(tile) {
+                    for (i, dir) in DIRECTIONS.iter().enumerate() {
+                        let next = navigation::neighbour(tile, *dir);
+
+                        // Stepping off the grid through a tunnel comes back in on the opposite edge.
+                        if passable(next) {
+                            let (next_x, next_y) = maze.wrap_tile(next);
+                            tile_links[i] = Some((next_y * width + next_x) as usize);
+                        }
+                    }
+                }
+
+                open.push(passable(tile));
+                links.push(tile_links);
+            }
+        }
+
+        Graph { width, height, open, links }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some((y * self.width + x) as usize)
+    }
+
+    pub fn is_open(&self, tile: (i32, i32)) -> bool {
+        self.index(tile).is_some_and(|index| self.open[index])
+    }
+
+    pub fn neighbours(&self, tile: (i32, i32)) -> impl Iterator<Item = ((i8, i8), (i32, i32))> + '_ {
+        let links = self.index(tile).map_or([None; 4], |index| self.links[index]);
+
+        DIRECTIONS
+            .iter()
+            .zip(links)
+            .filter_map(|(dir, link)| link.map(|index| (*dir, tile_of(self.width, index))))
+    }
+
+    pub fn distances(&self, from: (i32, i32)) -> DistanceMap {
+        let mut distances = vec![None; self.open.len()];
+        let mut queue = VecDeque::new();
+
+        if let Some(start) = self.index(from).filter(|index| self.open[*index]) {
+            distances[start] = Some(0);
+            queue.push_back(start);
+        }
+
+        while let Some(index) = queue.pop_front() {
+            let distance = distances[index].unwrap_or(0) + 1;
+
+            for next in self.links[index].iter().flatten() {
+                if distances[*next].is_none() {
+                    distances[*next] = Some(distance);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        DistanceMap {
+            width: self.width,
+            height: self.height,
+            distances
+        }
+    }
+
+    // Manhattan distance that allows for wrapping around the edges, so it never overestimates.
+    fn estimate(&self, (x, y): (i32, i32), (to_x, to_y): (i32, i32)) -> u32 {
+        let dx = (x - to_x).abs();
+        let dy = (y - to_y).abs();
+
+        (dx.min(self.width - dx) + dy.min(self.height - dy)) as u32
+    }
+
+    pub fn path(&self, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+        self.path_with(from, to, |_| Some(1))
+    }
+
+    // `cost` is the price of stepping onto a tile, or `None` to avoid it altogether.
+    pub fn path_with<F>(&self, from: (i32, i32), to: (i32, i32), mut cost: F) -> Option<Vec<(i32, i32)>>
+    where
+        F: FnMut((i32, i32)) -> Option<u32>
+    {
+        let start = self.index(from).filter(|index| self.open[*index])?;
+        let goal = self.index(to).filter(|index| self.open[*index])?;
+
+        let mut best = vec![u32::MAX; self.open.len()];
+        let mut came_from = vec![None; self.open.len()];
+        let mut open = BinaryHeap::new();
+
+        best[start] = 0;
+        open.push(Reverse((self.estimate(from, to), 0, start)));
+
+        while let Some(Reverse((_, spent, index))) = open.pop() {
+            if index == goal {
+                let mut path = vec![to];
+                let mut current = index;
+
+                while let Some(previous) = came_from[current] {
+                    path.push(tile_of(self.width, previous));
+                    current = previous;
+                }
+
+                path.reverse();
+                return Some(path);
+            }
+
+            if spent > best[index] {
+                continue;
+            }
+
+            for next in self.links[index].iter().flatten() {
+                let tile = tile_of(self.width, *next);
+                let Some(step) = cost(tile) else {
+                    continue;
+                };
+
+                let spent = spent + step.max(1);
+                if spent < best[*next] {
+                    best[*next] = spent;
+                    came_from[*next] = Some(index);
+                    open.push(Reverse((spent + self.estimate(tile, to), spent, *next)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::level::Level;
+
+    // The two halves of the top row only meet through the tunnel, and the tile at (2, 4) is walled in.
+    const MAZE: &str = "\
+pacman = 1 1
+map
+1111111
+t..1..t
+1..1..1
+1111111
+11.1111
+1111111
+";
+
+    fn graph() -> Graph {
+        let level = Level::parse(Path::new("test.lvl"), MAZE).expect("test maze is valid");
+
+        Graph::new(&Maze::from_level(&level), false)
+    }
+
+    fn steps(path: Option<Vec<(i32, i32)>>) -> Option<u32> {
+        path.map(|path| path.len() as u32 - 1)
+    }
+
+    #[test]
+    fn bfs_and_a_star_agree() {
+        let graph = graph();
+        let from = (1, 1);
+
+        for to in [(2, 1), (2, 2), (1, 2), (4, 2), (5, 1)] {
+            assert_eq!(steps(graph.path(from, to)), graph.distances(from).get(to), "to {:?}", to);
+        }
+    }
+
+    #[test]
+    fn paths_wrap_through_the_tunnel() {
+        let graph = graph();
+        let path = graph.path((1, 1), (5, 1));
+
+        assert_eq!(path, Some(vec![(1, 1), (0, 1), (6, 1), (5, 1)]));
+        assert_eq!(graph.distances((1, 1)).get((5, 1)), Some(3));
+    }
+
+    #[test]
+    fn unreachable_tile_has_no_path() {
+        let graph = graph();
+
+        assert!(graph.is_open((2, 4)));
+        assert_eq!(graph.path((1, 1), (2, 4)), None);
+        assert_eq!(graph.distances((1, 1)).get((2, 4)), None);
+    }
+}
@@ -0,0 +1,301 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::input::Action;
+
+pub struct Replay {
+    pub level: PathBuf,
+    pub stage: usize,
+    pub seed: u64,
+    pub score: u32,
+    pub lives: u32,
+    pub inputs: Vec<(u64, Action)>,
+    pub checksums: Vec<(u64, u64)>
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Divergence {
+    pub tick: u64,
+    pub expected: u64,
+    pub actual: u64
+}
+
+pub struct Playback {
+    replay: Replay,
+    next_input: usize,
+    next_checksum: usize,
+    divergence: Option<Divergence>
+}
+
+impl Replay {
+    pub fn new(level: &Path, stage: usize, seed: u64, score: u32, lives: u32) -> Replay {
+        Replay {
+            level: level.to_path_buf(),
+            stage,
+            seed,
+            score,
+            lives,
+            inputs: Vec::new(),
+            checksums: Vec::new()
+        }
+    }
+
+    pub fn length(&self) -> u64 {
+        let last_input = self.inputs.last().map(|(tick, _)| *tick).unwrap_or(0);
+        let last_checksum = self.checksums.last().map(|(tick, _)| *tick).unwrap_or(0);
+
+        last_input.max(last_checksum)
+    }
+
+    pub fn record_input(&mut self, tick: u64, action: Action) {
+        self.inputs.push((tick, action));
+    }
+
+    pub fn record_checksum(&mut self, tick: u64, checksum: u64) {
+        self.checksums.push((tick, checksum));
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut data = String::new();
+        data.push_str(&format!("level = {}\n", self.level.display()));
+        data.push_str(&format!("stage = {}\n", self.stage));
+        data.push_str(&format!("seed = {}\n", self.seed));
+        data.push_str(&format!("score = {}\n", self.score));
+        data.push_str(&format!("lives = {}\n", self.lives));
+
+        for (tick, action) in self.inputs.iter() {
+            data.push_str(&format!("act {} {}\n", tick, action.name()));
+        }
+
+        for (tick, checksum) in self.checksums.iter() {
+            data.push_str(&format!("sum {} {:016x}\n", tick, checksum));
+        }
+
+        fs::write(path, data)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        let data = fs::read_to_string(path)?;
+        let invalid = |line: usize, message: &str| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), line, message))
+        };
+
+        let mut replay = Replay::new(Path::new(""), 0, 0, 0, 0);
+
+        for (i, line) in data.lines().enumerate() {
+            let line_number = i + 1;
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+
+                match key.trim() {
+                    "level" => replay.level = PathBuf::from(value),
+                    "stage" => replay.stage = value.parse().map_err(|_| invalid(line_number, "bad stage"))?,
+                    "seed" => replay.seed = value.parse().map_err(|_| invalid(line_number, "bad seed"))?,
+                    "score" => replay.score = value.parse().map_err(|_| invalid(line_number, "bad score"))?,
+                    "lives" => replay.lives = value.parse().map_err(|_| invalid(line_number, "bad lives"))?,
+                    _ => return Err(invalid(line_number, "unknown replay key"))
+                }
+
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            match words.as_slice() {
+                [] => {}
+
+                ["act", tick, action] => {
+                    let tick = tick.parse().map_err(|_| invalid(line_number, "bad tick"))?;
+                    let action = Action::from_name(action)
+                        .ok_or_else(|| invalid(line_number, "bad action"))?;
+
+                    replay.record_input(tick, action);
+                }
+
+                ["sum", tick, checksum] => {
+                    let tick = tick.parse().map_err(|_| invalid(line_number, "bad tick"))?;
+                    let checksum = u64::from_str_radix(checksum, 16)
+                        .map_err(|_| invalid(line_number, "bad checksum"))?;
+
+                    replay.record_checksum(tick, checksum);
+                }
+
+                _ => return Err(invalid(line_number, "unknown replay line"))
+            }
+        }
+
+        if replay.level.as_os_str().is_empty() {
+            return Err(invalid(0, "missing level"));
+        }
+
+        replay.inputs.sort_by_key(|(tick, _)| *tick);
+        replay.checksums.sort_by_key(|(tick, _)| *tick);
+
+        Ok(replay)
+    }
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            next_input: 0,
+            next_checksum: 0,
+            divergence: None
+        }
+    }
+
+    pub fn inputs_at(&mut self, tick: u64) -> Vec<Action> {
+        let mut actions = Vec::new();
+
+        while let Some((input_tick, action)) = self.replay.inputs.get(self.next_input) {
+            if *input_tick > tick {
+                break;
+            }
+
+            actions.push(*action);
+            self.next_input += 1;
+        }
+
+        actions
+    }
+
+    pub fn check(&mut self, tick: u64, actual: u64) {
+        while let Some((checksum_tick, expected)) = self.replay.checksums.get(self.next_checksum) {
+            if *checksum_tick > tick {
+                break;
+            }
+
+            if *checksum_tick == tick && *expected != actual && self.divergence.is_none() {
+                self.divergence = Some(Divergence { tick, expected: *expected, actual });
+            }
+
+            self.next_checksum += 1;
+        }
+    }
+
+    pub fn finished(&self, tick: u64) -> bool {
+        tick >= self.replay.length()
+    }
+
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.divergence
+    }
+}
+
+pub fn checksum(values: &[i64]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for value in values.iter() {
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+    use crate::campaign::Difficulty;
+    use crate::game::Game;
+    use crate::headless::ScriptedInput;
+    use crate::level::{self, Level};
+
+    const LEVEL: &str = "levels/classic.lvl";
+    const TICKS: u64 = 600;
+
+    fn game(start: &Replay) -> Game {
+        let level = Level::load(&start.level).expect("classic level loads");
+        let mut game = Game::create("Pacman", 800, 600);
+
+        game.set_seed(start.seed);
+        game.set_score(start.score);
+        game.set_lives(start.lives);
+        game.set_level(start.stage + 1);
+        level::generate_map(&mut game, &level, &Difficulty::for_level(start.stage));
+
+        game
+    }
+
+    fn record(inputs: &[ScriptedInput]) -> Replay {
+        let start = Replay::new(Path::new(LEVEL), 0, 11, 0, 3);
+        let mut game = game(&start);
+
+        game.start_recording(start);
+        game.run_headless(TICKS, inputs);
+        game.take_recording().expect("the game was recording")
+    }
+
+    // Plays the replay back while recording it again, so the two sets of checksums can be compared.
+    fn play(replay: Replay) -> (Option<Divergence>, Replay) {
+        let start = Replay::new(&replay.level, replay.stage, replay.seed, replay.score, replay.lives);
+        let length = replay.length();
+        let mut game = game(&start);
+
+        game.start_recording(start);
+        game.start_playback(replay);
+        game.run_headless(length + 1, &[]);
+
+        (game.divergence(), game.take_recording().expect("the game was recording"))
+    }
+
+    fn inputs() -> Vec<ScriptedInput> {
+        [(0, Action::MoveRight), (200, Action::MoveUp), (300, Action::MoveLeft), (420, Action::MoveDown)]
+            .into_iter()
+            .map(|(tick, action)| ScriptedInput { tick, action })
+            .collect()
+    }
+
+    #[test]
+    fn saved_replay_plays_back_the_same() {
+        let recording = record(&inputs());
+        let path = env::temp_dir().join(format!("rust-pacman-{}.replay", process::id()));
+
+        recording.save(&path).expect("replay saves");
+        let loaded = Replay::load(&path);
+        fs::remove_file(&path).ok();
+        let loaded = loaded.expect("replay loads");
+
+        assert_eq!(loaded.inputs, recording.inputs);
+        assert_eq!(loaded.checksums, recording.checksums);
+
+        let (divergence, replayed) = play(loaded);
+
+        assert!(divergence.is_none());
+        assert_eq!(replayed.checksums, recording.checksums);
+    }
+
+    #[test]
+    fn changed_input_diverges_where_the_runs_split() {
+        let recording = record(&inputs());
+
+        let mut changed = inputs();
+        changed[2].action = Action::MoveRight;
+        let other = record(&changed);
+
+        let split = recording.checksums
+            .iter()
+            .zip(other.checksums.iter())
+            .find(|(a, b)| a != b)
+            .map(|((tick, _), _)| *tick)
+            .expect("the runs split");
+
+        let mut replay = recording;
+        let input = replay.inputs.iter_mut().find(|(tick, _)| *tick == changed[2].tick).expect("the input was recorded");
+        input.1 = Action::MoveRight;
+
+        let (divergence, _) = play(replay);
+        let divergence = divergence.expect("the replay diverges");
+
+        assert!(split > changed[2].tick);
+        assert_eq!(divergence.tick, split);
+    }
+}
@@ -0,0 +1,117 @@
+use std::io;
+
+use sdl2::{EventPump, Sdl};
+use sdl2::image::{InitFlag, Sdl2ImageContext};
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::video::{FullscreenType, Window};
+
+use crate::gamepad::Gamepads;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameRate {
+    Capped,
+    VSync,
+    Uncapped
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Display {
+    pub scale: u32,
+    pub fullscreen: bool
+}
+
+pub struct Screen {
+    sdl_context: Sdl,
+    width: u32,
+    height: u32,
+    display: Display,
+    _image_context: Sdl2ImageContext,
+    pub canvas: Canvas<Window>,
+    pub event_pump: EventPump,
+    pub gamepads: Gamepads
+}
+
+impl Screen {
+    pub fn open(title: &str, width: u32, height: u32, frame_rate: FrameRate, display: Display) -> io::Result<Screen> {
+        let sdl_context = sdl2::init()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let video_subsystem = sdl_context.video()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut window = video_subsystem.window(title, width * display.scale, height * display.scale);
+        window.position_centered();
+        if display.fullscreen {
+            window.fullscreen_desktop();
+        }
+
+        let window = window
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+        let mut builder = window.into_canvas();
+        if frame_rate == FrameRate::VSync {
+            builder = builder.present_vsync();
+        }
+
+        let mut canvas = builder
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+        let image_context = sdl2::image::init(InitFlag::JPG | InitFlag::PNG)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        canvas.set_logical_size(width, height)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+        canvas.present();
+
+        let event_pump = sdl_context
+            .event_pump()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let gamepads = Gamepads::open(&sdl_context);
+
+        Ok(Screen {
+            sdl_context,
+            width,
+            height,
+            display,
+            _image_context: image_context,
+            canvas,
+            event_pump,
+            gamepads
+        })
+    }
+
+    pub fn sdl(&self) -> &Sdl {
+        &self.sdl_context
+    }
+
+    pub fn set_display(&mut self, display: Display) {
+        if display == self.display {
+            return;
+        }
+
+        self.display = display;
+
+        let fullscreen = if display.fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+        let window = self.canvas.window_mut();
+
+        if let Err(e) = window.set_fullscreen(fullscreen) {
+            eprintln!("Can't change fullscreen mode: {}", e);
+        }
+
+        if let Err(e) = window.set_size(self.width * display.scale, self.height * display.scale) {
+            eprintln!("Can't resize window: {}", e);
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        if let Err(e) = self.canvas.window_mut().set_title(title) {
+            eprintln!("Can't set window title: {}", e);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sdl2::keyboard::Keycode;
+
+use crate::audio;
+use crate::campaign::START_LIVES;
+use crate::files;
+use crate::highscore::{self, APP_DIR};
+use crate::input::{Action, Bindings, ACTIONS};
+use crate::screen::Display;
+
+const SETTINGS_FILE: &str = "settings.txt";
+
+pub const MAX_SCALE: u32 = 4;
+pub const MAX_LIVES: u32 = 9;
+
+#[derive(Clone)]
+pub struct Settings {
+    path: Option<PathBuf>,
+    pub bindings: Bindings,
+    pub width: u32,
+    pub height: u32,
+    pub scale: u32,
+    pub fullscreen: bool,
+    pub volume: u8,
+    pub muted: bool,
+    pub lives: u32
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings::new(&Settings::default_path())
+    }
+}
+
+impl Settings {
+    pub fn new(path: &Path) -> Settings {
+        Settings {
+            path: Some(path.to_path_buf()),
+            ..Settings::unsaved()
+        }
+    }
+
+    // Defaults that are never written back, for when the file on disk is there but won't parse.
+    pub fn unsaved() -> Settings {
+        Settings {
+            path: None,
+            bindings: Bindings::default(),
+            width: 800,
+            height: 600,
+            scale: 1,
+            fullscreen: false,
+            volume: audio::MAX_VOLUME,
+            muted: false,
+            lives: START_LIVES
+        }
+    }
+
+    pub fn default_path() -> PathBuf {
+        highscore::data_dir().join(APP_DIR).join(SETTINGS_FILE)
+    }
+
+    pub fn display(&self) -> Display {
+        Display {
+            scale: self.scale,
+            fullscreen: self.fullscreen
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Settings> {
+        let mut settings = Settings::new(path);
+
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(settings),
+            Err(e) => return Err(e)
+        };
+
+        let invalid = |line: usize, message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), line, message))
+        };
+
+        for (i, line) in data.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(invalid(line_number, "expected `key = value`".to_string()));
+            };
+
+            let value = value.trim();
+            let number = |max: u32| {
+                value.parse::<u32>()
+                    .ok()
+                    .filter(|number| (1..=max).contains(number))
+                    .ok_or_else(|| invalid(line_number, format!("expected a number from 1 to {}, got `{}`", max, value)))
+            };
+            let flag = || match value {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(invalid(line_number, format!("expected true or false, got `{}`", value)))
+            };
+
+            match key.trim() {
+                "width" => settings.width = number(u16::MAX as u32)?,
+                "height" => settings.height = number(u16::MAX as u32)?,
+                "scale" => settings.scale = number(MAX_SCALE)?,
+                "fullscreen" => settings.fullscreen = flag()?,
+                "volume" => {
+                    settings.volume = value.parse::<u8>()
+                        .ok()
+                        .filter(|volume| *volume <= audio::MAX_VOLUME)
+                        .ok_or_else(|| invalid(line_number, format!("expected a volume from 0 to {}, got `{}`", audio::MAX_VOLUME, value)))?;
+                }
+                "muted" => settings.muted = flag()?,
+                "lives" => settings.lives = number(MAX_LIVES)?,
+
+                key => {
+                    let action = key.strip_prefix("bind ")
+                        .and_then(|name| Action::from_name(name.trim()))
+                        .ok_or_else(|| invalid(line_number, format!("unknown setting `{}`", key)))?;
+
+                    let keys = value.split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(|name| Keycode::from_name(name).ok_or_else(|| invalid(line_number, format!("unknown key `{}`", name))))
+                        .collect::<io::Result<Vec<Keycode>>>()?;
+
+                    settings.bindings.bind(action, keys);
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut data = String::new();
+        data.push_str(&format!("width = {}\n", self.width));
+        data.push_str(&format!("height = {}\n", self.height));
+        data.push_str(&format!("scale = {}\n", self.scale));
+        data.push_str(&format!("fullscreen = {}\n", self.fullscreen));
+        data.push_str(&format!("volume = {}\n", self.volume));
+        data.push_str(&format!("muted = {}\n", self.muted));
+        data.push_str(&format!("lives = {}\n", self.lives));
+
+        for action in ACTIONS {
+            let keys: Vec<String> = self.bindings.keys(action).iter().map(|key| key.name()).collect();
+            data.push_str(&format!("bind {} = {}\n", action.name(), keys.join(", ")));
+        }
+
+        files::write_atomic(path, &data)
+    }
+}
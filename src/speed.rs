@@ -0,0 +1,25 @@
+use crate::game::TICKS_PER_SECOND;
+use crate::level::BLOCK_SIZE;
+
+pub fn pixels_per_tick(tiles_per_second: f64) -> f64 {
+    tiles_per_second * BLOCK_SIZE as f64 / TICKS_PER_SECOND as f64
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Stride {
+    remainder: f64
+}
+
+impl Stride {
+    pub fn next(&mut self, tiles_per_second: f64) -> i32 {
+        let pixels = pixels_per_tick(tiles_per_second) + self.remainder;
+        let step = pixels.floor();
+
+        self.remainder = pixels - step;
+        step as i32
+    }
+
+    pub fn reset(&mut self) {
+        self.remainder = 0.0;
+    }
+}
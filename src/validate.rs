@@ -0,0 +1,292 @@
+use std::fs;
+use std::path::Path;
+
+use crate::level::{Level, LevelError, Tile};
+use crate::maze::Maze;
+use crate::navigation::{self, DIRECTIONS};
+use crate::pathfinding::Graph;
+
+struct Source<'a> {
+    path: &'a Path,
+    lines: Vec<&'a str>,
+    first_row: usize
+}
+
+impl Source<'_> {
+    fn at_tile(&self, (x, y): (i32, i32), message: &str) -> LevelError {
+        LevelError::new(self.path, self.first_row + y as usize, x as usize + 1, message)
+    }
+
+    fn at_key(&self, key: &str, first_word: Option<&str>, message: &str) -> LevelError {
+        let line = self.lines
+            .iter()
+            .position(|line| {
+                line.split_once('=').is_some_and(|(other, value)| {
+                    other.trim() == key && first_word.is_none_or(|word| value.split_whitespace().next() == Some(word))
+                })
+            })
+            .map_or(1, |index| index + 1);
+
+        LevelError::new(self.path, line, 1, message)
+    }
+
+    fn symbol(&self, (x, y): (i32, i32)) -> char {
+        self.lines
+            .get(self.first_row - 1 + y as usize)
+            .and_then(|line| line.chars().nth(x as usize))
+            .unwrap_or(' ')
+    }
+}
+
+fn side(dir: (i8, i8)) -> &'static str {
+    match dir {
+        (0, -1) => "up",
+        (-1, 0) => "left",
+        (0, 1) => "down",
+        _ => "right"
+    }
+}
+
+pub fn validate(path: &Path) -> Vec<LevelError> {
+    match fs::read_to_string(path) {
+        Ok(source) => validate_source(path, &source),
+        Err(e) => vec![LevelError::whole_file(path, &e.to_string())]
+    }
+}
+
+pub fn validate_source(path: &Path, source: &str) -> Vec<LevelError> {
+    let lines: Vec<&str> = source.lines().collect();
+    let map_line = lines.iter().position(|line| line.trim() == "map");
+
+    let mut problems = Vec::new();
+
+    // Report every unknown tile, then blank them out so the rest of the level can still be checked.
+    let mut cleaned: Vec<String> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if map_line.is_none_or(|map_line| i <= map_line) {
+            cleaned.push(line.to_string());
+            continue;
+        }
+
+        let mut row = String::new();
+        for (x, c) in line.chars().enumerate() {
+            if Tile::convert(c).is_some() {
+                row.push(c);
+            } else {
+                let message = format!("unknown tile character '{}'", c);
+                problems.push(LevelError::new(path, i + 1, x + 1, &message));
+                row.push(' ');
+            }
+        }
+
+        cleaned.push(row);
+    }
+
+    // Bad header lines are reported and dropped one at a time; anything else stops the check here.
+    let level = loop {
+        match Level::parse(path, &cleaned.join("\n")) {
+            Ok(level) => break level,
+            Err(e) => {
+                let index = e.position.and_then(|(line, _)| line.checked_sub(1)).filter(|index| map_line.is_some_and(|map_line| *index < map_line));
+                problems.push(e);
+
+                match index {
+                    Some(index) => cleaned[index].clear(),
+                    None => return problems
+                }
+            }
+        }
+    };
+
+    let source_info = Source {
+        path,
+        lines,
+        first_row: map_line.map_or(0, |index| index + 2)
+    };
+
+    check_shape(&source_info, &level, &mut problems);
+    check_spawns(&source_info, &level, &mut problems);
+    check_walls(&source_info, &level, &mut problems);
+    check_tunnels(&source_info, &level, &mut problems);
+    check_pellets(&source_info, &level, &mut problems);
+
+    problems
+}
+
+fn check_shape(source: &Source, level: &Level, problems: &mut Vec<LevelError>) {
+    let width = level.tiles.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    if width == 0 {
+        problems.push(LevelError::new(source.path, source.first_row, 1, "the map is empty"));
+        return;
+    }
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        if row.len() < width {
+            let message = format!("row is {} tiles wide, the widest row has {}", row.len(), width);
+            problems.push(source.at_tile((row.len() as i32, y as i32), &message));
+        }
+    }
+}
+
+fn check_spawns(source: &Source, level: &Level, problems: &mut Vec<LevelError>) {
+    let blocked = |pos: (i32, i32)| !level.is_passable(pos);
+
+    if blocked(level.pacman) {
+        let message = format!("pacman spawns at {:?}, which is a wall or outside the map", level.pacman);
+        problems.push(source.at_key("pacman", None, &message));
+    }
+
+    if level.ghosts.is_empty() {
+        problems.push(source.at_key("pacman", None, "the level has no ghosts"));
+    }
+
+    for ghost in &level.ghosts {
+        if blocked(ghost.spawn) {
+            let message = format!("{} spawns at {:?}, which is a wall or outside the map", ghost.personality.name(), ghost.spawn);
+            problems.push(source.at_key("ghost", Some(ghost.personality.name()), &message));
+        }
+    }
+
+    if let Some(fruit) = level.fruit
+        && blocked(fruit) {
+        let message = format!("the fruit appears at {:?}, which is a wall or outside the map", fruit);
+        problems.push(source.at_key("fruit", None, &message));
+    }
+}
+
+fn check_walls(source: &Source, level: &Level, problems: &mut Vec<LevelError>) {
+    let width = level.tiles.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+    let height = level.tiles.len() as i32;
+    let inside = |(x, y): (i32, i32)| x >= 0 && y >= 0 && x < width && y < height;
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, this) in row.iter().enumerate() {
+            let Tile::Wall(style) = this else {
+                continue;
+            };
+
+            let pos = (x as i32, y as i32);
+
+            for dir in DIRECTIONS.iter().copied().filter(|dir| style.connects(*dir)) {
+                let next = navigation::neighbour(pos, dir);
+
+                // The missing end of a short row is already reported as a ragged row.
+                if level.tile(next).is_none() && inside(next) {
+                    continue;
+                }
+
+                let joined = match level.tile(next) {
+                    Some(Tile::Wall(other)) => other.connects(navigation::reverse(dir)),
+                    _ => false
+                };
+
+                if !joined {
+                    let other = if level.tile(next).is_some() {
+                        format!("'{}'", source.symbol(next))
+                    } else {
+                        "the edge of the map".to_string()
+                    };
+
+                    let message = format!("wall '{}' reaches {} but {} doesn't join it", source.symbol(pos), side(dir), other);
+                    problems.push(source.at_tile(pos, &message));
+                }
+            }
+        }
+    }
+}
+
+fn check_tunnels(source: &Source, level: &Level, problems: &mut Vec<LevelError>) {
+    let height = level.tiles.len() as i32;
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        let width = row.len() as i32;
+
+        for (x, this) in row.iter().enumerate() {
+            if !matches!(this, Tile::Tunnel) {
+                continue;
+            }
+
+            let (x, y) = (x as i32, y as i32);
+            let exit = if x == 0 {
+                (width - 1, y)
+            } else if x == width - 1 {
+                (0, y)
+            } else if y == 0 {
+                (x, height - 1)
+            } else if y == height - 1 {
+                (x, 0)
+            } else {
+                problems.push(source.at_tile((x, y), "tunnel isn't on the edge of the map"));
+                continue;
+            };
+
+            if !matches!(level.tile(exit), Some(Tile::Tunnel)) {
+                let message = format!("tunnel has no matching exit at {:?}", exit);
+                problems.push(source.at_tile((x, y), &message));
+            }
+        }
+    }
+}
+
+fn check_pellets(source: &Source, level: &Level, problems: &mut Vec<LevelError>) {
+    let maze = Maze::from_level(level);
+    let reach = Graph::new(&maze, false).distances(level.pacman);
+
+    for (y, row) in level.tiles.iter().enumerate() {
+        for (x, this) in row.iter().enumerate() {
+            let pos = (x as i32, y as i32);
+
+            if matches!(this, Tile::Pellet | Tile::PowerPellet) && reach.get(pos).is_none() {
+                problems.push(source.at_tile(pos, "pacman can't reach this pellet"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(source: &str) -> Vec<String> {
+        validate_source(Path::new("test.lvl"), source)
+            .iter()
+            .map(|problem| problem.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_problem_in_one_file() {
+        let problems = problems("\
+pacman = 1 1
+colour = red
+map
+87779
+6.x.6
+6...6
+a777b
+");
+
+        assert_eq!(problems, [
+            "test.lvl:5:3: unknown tile character 'x'",
+            "test.lvl:2:1: unknown key `colour`",
+            "test.lvl:1:1: the level has no ghosts",
+        ]);
+    }
+
+    #[test]
+    fn short_rows_are_reported_without_panicking() {
+        let problems = problems("\
+pacman = 1 1
+ghost = blinky 2 1 1 1
+map
+87779
+6...6
+6.
+6...6
+a777b
+");
+
+        assert_eq!(problems, ["test.lvl:6:3: row is 2 tiles wide, the widest row has 5"]);
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use crate::entity::{Collider, Entity, EntityEnviroment, EntityId, GhostStatus, Message, Tag};
+use crate::events::GameEvent;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    pub id: EntityId,
+    pub tag: Tag,
+    pub pos: (i32, i32),
+    pub dir: (i8, i8),
+    pub collider: Option<Collider>,
+    pub frightened: bool
+}
+
+#[derive(Default)]
+pub struct World {
+    entities: Vec<Snapshot>
+}
+
+impl World {
+    pub fn capture(env: &EntityEnviroment) -> World {
+        let entities = env.iter()
+            .map(|(id, entity)| Snapshot {
+                id,
+                tag: entity.tag(),
+                pos: entity.get_pos(),
+                dir: entity.get_dir(),
+                collider: entity.get_collider(),
+                frightened: env.component::<GhostStatus>(id).is_some_and(|status| status.frightened)
+            })
+            .collect();
+
+        World { entities }
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Snapshot> {
+        self.entities
+            .binary_search_by_key(&id, |snapshot| snapshot.id)
+            .ok()
+            .map(|index| &self.entities[index])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Snapshot> {
+        self.entities.iter()
+    }
+
+    pub fn tagged(&self, tag: Tag) -> impl Iterator<Item = &Snapshot> {
+        self.entities.iter().filter(move |snapshot| snapshot.tag == tag)
+    }
+
+    pub fn first(&self, tag: Tag) -> Option<&Snapshot> {
+        self.tagged(tag).next()
+    }
+}
+
+pub enum Command {
+    Spawn(Box<dyn Entity>),
+    Despawn(EntityId),
+    Send(EntityId, Message),
+    Broadcast(Message),
+    Publish(GameEvent)
+}
+
+#[derive(Default)]
+pub struct Commands {
+    queue: VecDeque<Command>
+}
+
+impl Commands {
+    pub fn new() -> Commands {
+        Commands { queue: VecDeque::new() }
+    }
+
+    pub fn spawn(&mut self, entity: Box<dyn Entity>) {
+        self.queue.push_back(Command::Spawn(entity));
+    }
+
+    pub fn despawn(&mut self, id: EntityId) {
+        self.queue.push_back(Command::Despawn(id));
+    }
+
+    pub fn send(&mut self, id: EntityId, message: Message) {
+        self.queue.push_back(Command::Send(id, message));
+    }
+
+    pub fn broadcast(&mut self, message: Message) {
+        self.queue.push_back(Command::Broadcast(message));
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        self.queue.push_back(Command::Publish(event));
+    }
+
+    pub fn pop(&mut self) -> Option<Command> {
+        self.queue.pop_front()
+    }
+}
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use rust_pacman::campaign::Difficulty;
+use rust_pacman::game::{Game, GameState, TICKS_PER_SECOND};
+use rust_pacman::headless::ScriptedInput;
+use rust_pacman::input::Action;
+use rust_pacman::level::{self, Level};
+
+const SEED: u64 = 7;
+
+fn classic(lives: u32) -> Game {
+    let level = Level::load(Path::new("levels/classic.lvl")).expect("classic level loads");
+    let mut game = Game::create("Pacman", 800, 600);
+
+    game.set_seed(SEED);
+    game.set_lives(lives);
+    level::generate_map(&mut game, &level, &Difficulty::for_level(0));
+
+    game
+}
+
+#[test]
+fn ready_countdown_starts_play() {
+    let mut game = classic(3);
+    game.run_headless(TICKS_PER_SECOND as u64, &[]);
+    assert_eq!(game.state(), GameState::Ready);
+
+    let mut game = classic(3);
+    game.run_headless(3 * TICKS_PER_SECOND as u64, &[]);
+    assert_eq!(game.state(), GameState::Playing);
+}
+
+#[test]
+fn eating_pellets_scores() {
+    let mut game = classic(3);
+    let pellets = game.world_state().pellets.len();
+    let inputs = [ScriptedInput { tick: 0, action: Action::MoveRight }];
+
+    let run = game.run_headless(5 * TICKS_PER_SECOND as u64, &inputs);
+
+    assert!(run.world.pellets.len() < pellets);
+    assert!(run.world.score > 0);
+}
+
+#[test]
+fn losing_every_life_ends_the_game() {
+    let mut game = classic(2);
+    let run = game.run_headless(60 * TICKS_PER_SECOND as u64, &[]);
+
+    assert_eq!(run.state, Some(GameState::Lose));
+    assert_eq!(run.world.lives, 0);
+}